@@ -0,0 +1,182 @@
+//! An on-disk cache of the answers, so a repeated run can skip the days
+//! whose solver and input did not change
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The enabled features that can change an answer, like `wide-calories` which
+/// sums day 1 into a `u128` instead of failing on a `u64` overflow
+fn features() -> String {
+    let features: &[&str] = &[
+        #[cfg(feature = "wide-calories")]
+        "wide-calories",
+    ];
+    features.join(",")
+}
+
+/// What the answers of a day depend on
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Key {
+    day: u8,
+    version: u32,
+    input: u64,
+    features: String,
+}
+
+impl Key {
+    pub fn new(day: u8, version: u32, input: &str) -> Self {
+        Self {
+            day,
+            version,
+            input: fnv1a(input.as_bytes()),
+            features: features(),
+        }
+    }
+}
+
+/// A hash that stays the same between builds, unlike the one of `HashMap`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &v| {
+        (hash ^ v as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    #[serde(default)]
+    entry: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    day: u8,
+    version: u32,
+    /// The hash of the input, as hex because TOML integers are signed
+    input: String,
+    /// `None` in the caches written before the features were part of the key
+    features: Option<String>,
+    answers: [String; 2],
+}
+
+pub struct Cache {
+    path: PathBuf,
+    entries: Mutex<HashMap<Key, [String; 2]>>,
+}
+
+impl Cache {
+    /// An unreadable cache file is the same as an empty one, it is overwritten by the next answer
+    pub fn load(path: &Path) -> Self {
+        let file = match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Ignoring the invalid cache `{}`: {e}", path.display());
+                CacheFile::default()
+            }),
+            Err(_) => CacheFile::default(),
+        };
+
+        let entries = file
+            .entry
+            .into_iter()
+            .filter_map(|v| {
+                let input = u64::from_str_radix(&v.input, 16).ok()?;
+                let key = Key {
+                    day: v.day,
+                    version: v.version,
+                    input,
+                    features: v.features?,
+                };
+
+                Some((key, v.answers))
+            })
+            .collect();
+
+        Self {
+            path: path.to_owned(),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn get(&self, key: &Key) -> Option<[String; 2]> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    /// Stores the answers as the only ones of that day, for the same features, and
+    /// writes the cache file
+    pub fn insert(&self, key: Key, answers: [String; 2]) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|v, _| v.day != key.day || v.features != key.features);
+        entries.insert(key, answers);
+
+        if let Err(e) = self.save(&entries) {
+            log::warn!("Cannot write the cache `{}`: {e}", self.path.display());
+        }
+    }
+
+    fn save(&self, entries: &HashMap<Key, [String; 2]>) -> io::Result<()> {
+        let mut entry: Vec<Entry> = entries
+            .iter()
+            .map(|(key, answers)| Entry {
+                day: key.day,
+                version: key.version,
+                input: format!("{:016x}", key.input),
+                features: Some(key.features.clone()),
+                answers: answers.clone(),
+            })
+            .collect();
+        entry.sort_unstable_by_key(|v| v.day);
+
+        let content = toml::to_string(&CacheFile { entry })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(&self.path, content)
+    }
+
+    /// Removes the cache file, `false` when there was none
+    pub fn clear(path: &Path) -> io::Result<bool> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn round_trip() {
+        let path = env::temp_dir().join(format!("aoc-cache-{}.toml", std::process::id()));
+        let key = Key::new(1, 1, "1000\n2000");
+        let answers = ["3000".to_owned(), "multi\nline".to_owned()];
+
+        let cache = Cache::load(&path);
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), answers.clone());
+
+        let cache = Cache::load(&path);
+        assert_eq!(cache.get(&key), Some(answers.clone()));
+        assert_eq!(cache.get(&Key::new(1, 2, "1000\n2000")), None);
+        assert_eq!(cache.get(&Key::new(1, 1, "1000\n2001")), None);
+
+        // the answers of a build with other features are kept, but not reused
+        let other = Key {
+            features: "other".to_owned(),
+            ..key.clone()
+        };
+        assert_eq!(cache.get(&other), None);
+        cache.insert(other.clone(), ["1".to_owned(), "2".to_owned()]);
+
+        let cache = Cache::load(&path);
+        assert_eq!(cache.get(&key), Some(answers));
+        assert!(cache.get(&other).is_some());
+
+        assert!(Cache::clear(&path).unwrap());
+        assert!(!Cache::clear(&path).unwrap());
+    }
+}
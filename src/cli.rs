@@ -0,0 +1,227 @@
+//! Command line arguments of the runner
+
+use crate::output::{ColorChoice, OutputMode};
+use std::env;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: advent-of-code-2022 [OPTIONS] [DAY...]
+       advent-of-code-2022 [OPTIONS] cache clear
+       advent-of-code-2022 [OPTIONS] query <DAY> <NAME> [ARG...]
+
+Runs the solutions of the given days, or every day when none is given.
+`cache clear` removes the cache file of the answers.
+`query` prints an extra report about the input of a day, e.g. `query 1 ranking 10`.
+
+Options:
+  -b, --benchmark            write the timings into the benchmark file
+  -r, --report               write an HTML report into the report file
+  -t, --tui                  show a full-screen dashboard while running
+  -l, --log                  write the log into the log file
+  -j, --jobs <N>             run up to N days at the same time [default: 1]
+      --parallel-parts       solve part 1 and part 2 of a day at the same time
+  -s, --stream               read the inputs line by line, for the days that can
+  -q, --quiet                print only the answers
+  -a, --answers              print `day part answer` for every answer
+      --color <WHEN>         colorize the output: auto, always or never [default: auto]
+  -c, --config <PATH>        read the settings from this file [default: aoc.toml]
+      --year <YEAR>          the year of the puzzles, used for the links [default: 2022]
+      --input-dir <DIR>      where the `dayXX.txt` inputs are [default: input]
+      --answers-file <PATH>  the `day part answer` file of the expected answers
+      --timeout <DURATION>   give up on a day after this long, e.g. `30s`
+      --samples <N>          run every day N times for the benchmark [default: 1]
+      --benchmark-file <PATH>  [default: benchmark.md]
+      --report-file <PATH>     [default: report.html]
+      --log-file <PATH>        [default: output.log]
+      --cache                reuse the answers of unchanged days from the cache file
+      --no-cache             solve every day, even when the cache is enabled
+      --cache-file <PATH>      [default: .aoc-cache.toml]
+  -h, --help                 print this help
+
+Settings are taken from the defaults, then `aoc.toml`, then the `AOC_*`
+environment variables, then the flags above, the later one wins.";
+
+/// An extra report about a day, see `AdventOfCode::query`
+pub struct Query {
+    pub day: u8,
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct Args {
+    pub days: Vec<u8>,
+    pub benchmark: bool,
+    pub report: bool,
+    pub dashboard: bool,
+    pub log: bool,
+    pub jobs: usize,
+    pub parallel_parts: bool,
+    pub stream: bool,
+    pub output: OutputMode,
+    pub help: bool,
+    /// Remove the cache file instead of running
+    pub clear_cache: bool,
+    /// Run this query instead of solving the days
+    pub query: Option<Query>,
+    // overrides of the settings in `Config`
+    pub config: Option<PathBuf>,
+    pub color: Option<ColorChoice>,
+    pub year: Option<u16>,
+    pub input_dir: Option<PathBuf>,
+    pub answers_file: Option<PathBuf>,
+    pub timeout: Option<Duration>,
+    pub samples: Option<usize>,
+    pub benchmark_file: Option<PathBuf>,
+    pub report_file: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub cache: Option<bool>,
+    pub cache_file: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut res = Self {
+            jobs: 1,
+            ..Self::default()
+        };
+        let mut iter = args.into_iter();
+
+        while let Some(arg) = iter.next() {
+            // `--flag=value` is the same as `--flag value`
+            let (flag, mut inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg, None),
+            };
+
+            let mut value = |name: &str| {
+                inline
+                    .take()
+                    .or_else(|| iter.next())
+                    .ok_or_else(|| format!("`{name}` needs a value"))
+            };
+
+            match flag.as_str() {
+                "-b" | "--benchmark" => res.benchmark = true,
+                "-r" | "--report" => res.report = true,
+                "-t" | "--tui" => res.dashboard = true,
+                "-l" | "--log" => res.log = true,
+                "-j" | "--jobs" => {
+                    res.jobs = match value("--jobs")?.parse() {
+                        Ok(0) | Err(_) => return Err("`--jobs` needs a positive number".into()),
+                        Ok(jobs) => jobs,
+                    }
+                }
+                "--parallel-parts" => res.parallel_parts = true,
+                "-s" | "--stream" => res.stream = true,
+                "-q" | "--quiet" => res.output = OutputMode::Quiet,
+                "-a" | "--answers" => res.output = OutputMode::Answers,
+                "--color" => res.color = Some(value("--color")?.parse()?),
+                "-c" | "--config" => res.config = Some(value("--config")?.into()),
+                "--year" => res.year = Some(parse("--year", value("--year")?)?),
+                "--input-dir" => res.input_dir = Some(value("--input-dir")?.into()),
+                "--answers-file" => res.answers_file = Some(value("--answers-file")?.into()),
+                "--timeout" => {
+                    let timeout = humantime::parse_duration(&value("--timeout")?)
+                        .map_err(|e| format!("invalid `--timeout`: {e}"))?;
+                    res.timeout = Some(timeout);
+                }
+                "--samples" => res.samples = Some(parse("--samples", value("--samples")?)?),
+                "--benchmark-file" => res.benchmark_file = Some(value("--benchmark-file")?.into()),
+                "--report-file" => res.report_file = Some(value("--report-file")?.into()),
+                "--log-file" => res.log_file = Some(value("--log-file")?.into()),
+                "--cache" => res.cache = Some(true),
+                "--no-cache" => res.cache = Some(false),
+                "--cache-file" => res.cache_file = Some(value("--cache-file")?.into()),
+                "-h" | "--help" => res.help = true,
+                "query" => {
+                    let day = iter.next().ok_or("`query` needs a day")?;
+                    let name = iter.next().ok_or("`query` needs the name of a query")?;
+
+                    // everything after the name belongs to the query
+                    res.query = Some(Query {
+                        day: parse("query", day)?,
+                        name,
+                        args: iter.by_ref().collect(),
+                    });
+                }
+                "cache" => match iter.next().as_deref() {
+                    Some("clear") => res.clear_cache = true,
+                    _ => return Err("`cache` needs a command: clear".into()),
+                },
+                day => match day.parse::<u8>() {
+                    Ok(day) => res.days.push(day),
+                    Err(_) => return Err(format!("unknown argument `{day}`")),
+                },
+            }
+        }
+
+        if res.days.is_empty() {
+            res.days = (1..=25).collect();
+        }
+
+        Ok(res)
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: String) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    value.parse().map_err(|e| format!("invalid `{name}`: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, String> {
+        Args::parse(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn flags() {
+        let args = parse("-q --color=never --jobs 4 --timeout 30s 3 1").unwrap();
+        assert_eq!(args.output, OutputMode::Quiet);
+        assert_eq!(args.color, Some(ColorChoice::Never));
+        assert_eq!(args.jobs, 4);
+        assert_eq!(args.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(args.days, [3, 1]);
+
+        let args = parse("--cache --no-cache").unwrap();
+        assert_eq!((args.cache, args.days.len()), (Some(false), 25));
+    }
+
+    #[test]
+    fn commands() {
+        let query = parse("-a query 1 ranking 10 --quiet")
+            .unwrap()
+            .query
+            .unwrap();
+        assert_eq!((query.day, query.name.as_str()), (1, "ranking"));
+        assert_eq!(query.args, ["10", "--quiet"]);
+
+        assert!(parse("cache clear").unwrap().clear_cache);
+        assert!(parse("cache").is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            parse("--jobs 0").err().unwrap(),
+            "`--jobs` needs a positive number"
+        );
+        assert_eq!(parse("--color").err().unwrap(), "`--color` needs a value");
+        assert!(parse("--color sometimes").is_err());
+        assert_eq!(parse("--what").err().unwrap(), "unknown argument `--what`");
+    }
+}
@@ -0,0 +1,366 @@
+//! Settings of the runner, resolved from (lowest to highest precedence) the
+//! defaults, the `aoc.toml` file, the `AOC_*` environment variables and the
+//! command line flags.
+
+use crate::cli::Args;
+use crate::output::ColorChoice;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const DEFAULT_PATH: &str = "aoc.toml";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub year: u16,
+    pub input_dir: PathBuf,
+    /// A file of the expected answers, one `day part answer` per line
+    pub answers: Option<PathBuf>,
+    /// Stop waiting for a day after this long, e.g. `"30s"`
+    #[serde(deserialize_with = "duration")]
+    pub timeout: Option<Duration>,
+    #[serde(deserialize_with = "from_str")]
+    pub color: ColorChoice,
+    pub benchmark: BenchmarkConfig,
+    pub report: ReportConfig,
+    pub log: LogConfig,
+    pub cache: CacheConfig,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BenchmarkConfig {
+    /// How many times every day is run, the median timing is written
+    pub samples: usize,
+    pub output: PathBuf,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReportConfig {
+    pub output: PathBuf,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub enabled: bool,
+    pub file: PathBuf,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Reuse the answers of a day while its input and solver version stay the same
+    pub enabled: bool,
+    pub file: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            year: 2022,
+            input_dir: PathBuf::from("input"),
+            answers: None,
+            timeout: None,
+            color: ColorChoice::Auto,
+            benchmark: BenchmarkConfig::default(),
+            report: ReportConfig::default(),
+            log: LogConfig::default(),
+            cache: CacheConfig::default(),
+        }
+    }
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            samples: 1,
+            output: PathBuf::from("benchmark.md"),
+        }
+    }
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            output: PathBuf::from("report.html"),
+        }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: PathBuf::from("output.log"),
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: PathBuf::from(".aoc-cache.toml"),
+        }
+    }
+}
+
+impl Config {
+    /// Resolves the settings for the given command line arguments
+    pub fn resolve(args: &Args) -> Result<Self, String> {
+        // only the `AOC_*` variables, any other one may not even be Unicode
+        let vars: HashMap<String, OsString> = env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value)))
+            .filter(|(name, _)| name.starts_with("AOC_"))
+            .collect();
+        let path = args
+            .config
+            .clone()
+            .or_else(|| vars.get("AOC_CONFIG").map(PathBuf::from));
+
+        let mut config = match path {
+            Some(path) => Self::load(&path)?,
+            None if Path::new(DEFAULT_PATH).exists() => Self::load(Path::new(DEFAULT_PATH))?,
+            None => Self::default(),
+        };
+
+        config.apply_env(&vars)?;
+        config.apply_args(args);
+
+        if config.benchmark.samples == 0 {
+            return Err("the benchmark needs at least 1 sample".into());
+        }
+
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read `{}`: {e}", path.display()))?;
+
+        toml::from_str(&content).map_err(|e| format!("invalid `{}`: {e}", path.display()))
+    }
+
+    fn apply_env(&mut self, vars: &HashMap<String, OsString>) -> Result<(), String> {
+        fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String>
+        where
+            T::Err: Display,
+        {
+            value.parse().map_err(|e| format!("invalid `{name}`: {e}"))
+        }
+
+        fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+            match value.to_ascii_lowercase().as_str() {
+                "1" | "yes" | "true" => Ok(true),
+                "0" | "no" | "false" => Ok(false),
+                _ => Err(format!(
+                    "invalid `{name}`: expected 1, 0, yes, no, true or false"
+                )),
+            }
+        }
+
+        for (name, value) in vars {
+            // paths can be any OS string, the other settings are text
+            let text = || {
+                value
+                    .to_str()
+                    .ok_or_else(|| format!("invalid `{name}`: not valid Unicode"))
+            };
+
+            match name.as_str() {
+                "AOC_YEAR" => self.year = parse(name, text()?)?,
+                "AOC_INPUT_DIR" => self.input_dir = PathBuf::from(value),
+                "AOC_ANSWERS" => self.answers = Some(PathBuf::from(value)),
+                "AOC_TIMEOUT" => {
+                    self.timeout = Some(
+                        humantime::parse_duration(text()?)
+                            .map_err(|e| format!("invalid `{name}`: {e}"))?,
+                    )
+                }
+                "AOC_COLOR" => self.color = parse(name, text()?)?,
+                "AOC_BENCHMARK_SAMPLES" => self.benchmark.samples = parse(name, text()?)?,
+                "AOC_BENCHMARK_OUTPUT" => self.benchmark.output = PathBuf::from(value),
+                "AOC_REPORT_OUTPUT" => self.report.output = PathBuf::from(value),
+                "AOC_LOG" => self.log.enabled = parse_bool(name, text()?)?,
+                "AOC_LOG_FILE" => self.log.file = PathBuf::from(value),
+                "AOC_CACHE" => self.cache.enabled = parse_bool(name, text()?)?,
+                "AOC_CACHE_FILE" => self.cache.file = PathBuf::from(value),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(year) = args.year {
+            self.year = year;
+        }
+
+        if let Some(input_dir) = &args.input_dir {
+            self.input_dir = input_dir.clone();
+        }
+
+        if let Some(answers) = &args.answers_file {
+            self.answers = Some(answers.clone());
+        }
+
+        if let Some(timeout) = args.timeout {
+            self.timeout = Some(timeout);
+        }
+
+        if let Some(color) = args.color {
+            self.color = color;
+        }
+
+        if let Some(samples) = args.samples {
+            self.benchmark.samples = samples;
+        }
+
+        if let Some(output) = &args.benchmark_file {
+            self.benchmark.output = output.clone();
+        }
+
+        if let Some(output) = &args.report_file {
+            self.report.output = output.clone();
+        }
+
+        if args.log {
+            self.log.enabled = true;
+        }
+
+        if let Some(file) = &args.log_file {
+            self.log.file = file.clone();
+        }
+
+        if let Some(enabled) = args.cache {
+            self.cache.enabled = enabled;
+        }
+
+        if let Some(file) = &args.cache_file {
+            self.cache.file = file.clone();
+        }
+
+        // cached answers have no timings to benchmark
+        if args.benchmark {
+            self.cache.enabled = false;
+        }
+    }
+}
+
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+fn duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    humantime::parse_duration(&value)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        let mut config: Config = toml::from_str(
+            r#"
+            input_dir = "from-file"
+            answers = "from-file.txt"
+            timeout = "2s"
+
+            [benchmark]
+            samples = 3
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.input_dir, Path::new("from-file"));
+        assert_eq!(config.answers.as_deref(), Some(Path::new("from-file.txt")));
+        assert_eq!(config.timeout, Some(Duration::from_secs(2)));
+        assert_eq!(config.benchmark.samples, 3);
+        assert_eq!(config.report, ReportConfig::default());
+
+        let vars = HashMap::from([
+            ("AOC_INPUT_DIR".to_owned(), "from-env".into()),
+            ("AOC_BENCHMARK_SAMPLES".to_owned(), "5".into()),
+            ("AOC_ANSWERS".to_owned(), "from-env.txt".into()),
+        ]);
+        config.apply_env(&vars).unwrap();
+
+        assert_eq!(config.input_dir, Path::new("from-env"));
+        assert_eq!(config.answers.as_deref(), Some(Path::new("from-env.txt")));
+        assert_eq!(config.benchmark.samples, 5);
+
+        let args = [
+            "--input-dir",
+            "from-args",
+            "--answers-file",
+            "from-args.txt",
+        ];
+        config.apply_args(&Args::parse(args.map(String::from)).unwrap());
+
+        assert_eq!(config.input_dir, Path::new("from-args"));
+        assert_eq!(config.answers.as_deref(), Some(Path::new("from-args.txt")));
+        assert_eq!(config.benchmark.samples, 5);
+        assert_eq!(config.timeout, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn env_booleans() {
+        let mut config = Config::default();
+
+        for (value, enabled) in [("1", true), ("no", false), ("YES", true), ("false", false)] {
+            let vars = HashMap::from([("AOC_LOG".to_owned(), value.into())]);
+            config.apply_env(&vars).unwrap();
+            assert_eq!(config.log.enabled, enabled);
+        }
+
+        let vars = HashMap::from([("AOC_CACHE".to_owned(), "maybe".into())]);
+        assert!(config.apply_env(&vars).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn env_not_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let value = OsString::from_vec(b"dir-\xff".to_vec());
+        let mut config = Config::default();
+
+        let vars = HashMap::from([("AOC_INPUT_DIR".to_owned(), value.clone())]);
+        config.apply_env(&vars).unwrap();
+        assert_eq!(config.input_dir.as_os_str(), value);
+
+        let vars = HashMap::from([("AOC_YEAR".to_owned(), value)]);
+        assert_eq!(
+            config.apply_env(&vars),
+            Err("invalid `AOC_YEAR`: not valid Unicode".to_owned())
+        );
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert!(toml::from_str::<Config>("inputs = \"typo\"").is_err());
+    }
+}
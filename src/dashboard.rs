@@ -0,0 +1,611 @@
+//! A full-screen terminal dashboard that shows the state of every day while
+//! the solutions are running.
+
+use crate::cli::Args;
+use crate::config::Config;
+use crate::{display_benchmark_time, logger, Progress, RunOptions, Timing};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::cell::Cell;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const COLUMNS: u16 = 5;
+const CELL_WIDTH: u16 = 16;
+const GRID_TOP: u16 = 2;
+const DETAIL_TOP: u16 = GRID_TOP + 25 / COLUMNS + 1;
+/// How many of the last lines of the runner log are shown
+const RUNNER_LOG_LINES: usize = 3;
+
+thread_local! {
+    /// The day a worker thread is running, so that its log lines go to that day
+    static DAY: Cell<Option<u8>> = const { Cell::new(None) };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pending,
+    Running,
+    Solved,
+    Failed,
+    Missing,
+}
+
+impl Status {
+    fn symbol(self) -> char {
+        match self {
+            Self::Pending => '·',
+            Self::Running => '…',
+            Self::Solved => '✔',
+            Self::Failed => '✘',
+            Self::Missing => '-',
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Pending => Color::Grey,
+            Self::Running => Color::Yellow,
+            Self::Solved => Color::Green,
+            Self::Failed => Color::Red,
+            Self::Missing => Color::DarkGrey,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Solved => "solved",
+            Self::Failed => "failed",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+enum Event {
+    Running(u8),
+    Progress(u8, Progress),
+    /// A log line, of the day of the thread that wrote it when it has one
+    Log(Option<u8>, String),
+    Finished(u8, Option<Timing>),
+}
+
+struct DayState {
+    status: Status,
+    title: Option<&'static str>,
+    parsing: Option<Duration>,
+    parts: [Option<(String, Duration)>; 2],
+    logs: Vec<String>,
+}
+
+impl DayState {
+    fn new(status: Status) -> Self {
+        Self {
+            status,
+            title: None,
+            parsing: None,
+            parts: [None, None],
+            logs: Vec::new(),
+        }
+    }
+
+    fn total(&self) -> Option<Duration> {
+        let parts = self.parts.iter().flatten().map(|v| v.1);
+        Some(self.parsing? + parts.sum::<Duration>())
+    }
+}
+
+struct Dashboard {
+    days: Vec<DayState>,
+    timings: Vec<Option<Timing>>,
+    running: Vec<u8>,
+    /// How many of the given days have not finished yet
+    remaining: usize,
+    /// The log lines of no day, like the warnings about the cache
+    logs: Vec<String>,
+    selected: u8,
+    /// The selection follows the running day until the user moves it
+    follow: bool,
+    year: u16,
+}
+
+impl Dashboard {
+    fn new(days: &[u8], year: u16) -> Self {
+        let days_count = days.len();
+        let days = (1..=25)
+            .map(|day| match days.contains(&day) {
+                true => DayState::new(Status::Pending),
+                false => DayState::new(Status::Missing),
+            })
+            .collect();
+
+        Self {
+            days,
+            timings: vec![None; 25],
+            running: Vec::new(),
+            remaining: days_count,
+            logs: Vec::new(),
+            selected: 1,
+            follow: true,
+            year,
+        }
+    }
+
+    fn day_mut(&mut self, day: u8) -> Option<&mut DayState> {
+        self.days.get_mut((day as usize).checked_sub(1)?)
+    }
+
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::Running(day) => {
+                self.running.push(day);
+
+                if let Some(state) = self.day_mut(day) {
+                    state.status = Status::Running;
+                }
+            }
+            Event::Progress(day, progress) => {
+                if self.follow && matches!(progress, Progress::Started(_)) {
+                    self.selected = day;
+                }
+
+                let Some(state) = self.day_mut(day) else {
+                    return;
+                };
+
+                match progress {
+                    Progress::Started(title) => state.title = Some(title),
+                    Progress::NoSolution | Progress::InvalidDay | Progress::MissingInput => {
+                        state.status = Status::Missing
+                    }
+                    Progress::ParseFailed(e) => {
+                        state.status = Status::Failed;
+                        state.logs.push(format!("Cannot parse the input: {e}"));
+                    }
+                    Progress::TimedOut(_) => state.status = Status::Failed,
+                    Progress::Parsed(time) => state.parsing = Some(time),
+                    Progress::Cached => {
+                        state.parsing = Some(Duration::ZERO);
+                        state.logs.push("Answers read from the cache".to_owned());
+                    }
+                    Progress::Solved(part, answer, time) => {
+                        state.parts[part as usize - 1] = Some((answer, time))
+                    }
+                }
+            }
+            Event::Log(day, line) => {
+                // a thread of its own, like the one of a timeout, only knows its day
+                // when it is the only one running
+                let day = day.or(match self.running[..] {
+                    [day] => Some(day),
+                    _ => None,
+                });
+
+                match day.and_then(|day| self.day_mut(day)) {
+                    Some(state) => state.logs.push(line),
+                    None => self.logs.push(line),
+                }
+            }
+            Event::Finished(day, timing) => {
+                self.running.retain(|&v| v != day);
+                self.remaining = self.remaining.saturating_sub(1);
+
+                let Some(state) = self.day_mut(day) else {
+                    return;
+                };
+
+                if timing.is_some() {
+                    state.status = Status::Solved;
+                } else if state.status == Status::Running {
+                    // it did not report why, so it panicked
+                    state.status = Status::Failed;
+                }
+
+                self.timings[day as usize - 1] = timing;
+            }
+        }
+    }
+
+    /// Every given day has finished
+    fn done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn select(&mut self, offset: i8) {
+        let selected = self.selected as i8 + offset;
+
+        if (1..=25).contains(&selected) {
+            self.selected = selected as u8;
+            self.follow = false;
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let solved = self
+            .days
+            .iter()
+            .filter(|v| v.status == Status::Solved)
+            .count();
+
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(1, 0),
+            SetAttribute(Attribute::Bold),
+            Print(format!("Advent of Code {}", self.year)),
+            SetAttribute(Attribute::Reset),
+            Print(format!(" - {solved}/25 solved")),
+        )?;
+
+        if self.done() {
+            queue!(
+                out,
+                SetForegroundColor(Color::Green),
+                Print(" - done, press q to quit"),
+                ResetColor,
+            )?;
+        }
+
+        for (state, day) in self.days.iter().zip(1u8..) {
+            let column = (day as u16 - 1) % COLUMNS;
+            let row = (day as u16 - 1) / COLUMNS;
+            let time = state
+                .total()
+                .map(display_benchmark_time)
+                .unwrap_or_default();
+
+            queue!(out, MoveTo(1 + column * CELL_WIDTH, GRID_TOP + row))?;
+
+            if day == self.selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+
+            queue!(
+                out,
+                SetForegroundColor(state.status.color()),
+                Print(format!(
+                    "{day:02} {} {:<10}",
+                    state.status.symbol(),
+                    time.trim()
+                )),
+                ResetColor,
+                SetAttribute(Attribute::Reset),
+            )?;
+        }
+
+        let state = &self.days[self.selected as usize - 1];
+        let mut lines = Vec::new();
+
+        lines.push(format!(
+            "Day {} - {} ({})",
+            self.selected,
+            state.title.unwrap_or("?"),
+            state.status.name()
+        ));
+
+        if let Some(time) = state.parsing {
+            lines.push(format!("Parse  : {}", display_benchmark_time(time)));
+        }
+
+        for (part, number) in state.parts.iter().zip(1..) {
+            let Some((answer, time)) = part else { continue };
+            let time = display_benchmark_time(*time);
+
+            if answer.contains('\n') {
+                lines.push(format!("Part {number} : ({time})"));
+                lines.extend(answer.lines().map(|v| format!("  {v}")));
+            } else {
+                lines.push(format!("Part {number} : {answer} ({time})"));
+            }
+        }
+
+        let runner_logs = &self.logs[self.logs.len().saturating_sub(RUNNER_LOG_LINES)..];
+
+        if !state.logs.is_empty() {
+            lines.push(String::new());
+            lines.push("Log:".to_owned());
+
+            let available = (height.saturating_sub(DETAIL_TOP + 2) as usize)
+                .saturating_sub(lines.len() + runner_logs.len());
+            let skip = state.logs.len().saturating_sub(available);
+            lines.extend(state.logs.iter().skip(skip).map(|v| format!("  {v}")));
+        }
+
+        queue!(
+            out,
+            MoveTo(0, DETAIL_TOP),
+            Print("─".repeat(width as usize))
+        )?;
+
+        let clip = |line: &str| -> String {
+            line.chars()
+                .take(width.saturating_sub(1) as usize)
+                .collect()
+        };
+        let runner_top = height.saturating_sub(1 + runner_logs.len() as u16);
+
+        for (line, row) in lines.iter().zip(DETAIL_TOP + 1..runner_top) {
+            queue!(out, MoveTo(1, row), Print(clip(line)))?;
+        }
+
+        for (line, row) in runner_logs.iter().zip(runner_top..) {
+            queue!(
+                out,
+                MoveTo(1, row),
+                SetForegroundColor(Color::Yellow),
+                Print(clip(line)),
+                ResetColor,
+            )?;
+        }
+
+        queue!(
+            out,
+            MoveTo(1, height.saturating_sub(1)),
+            SetForegroundColor(Color::DarkGrey),
+            Print("arrows/hjkl: select day   q: quit"),
+            ResetColor,
+        )?;
+
+        out.flush()
+    }
+}
+
+/// Restores the terminal even when the dashboard returns early
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        execute!(io::stdout(), Show, LeaveAlternateScreen).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+/// Runs the days on `jobs` threads, each one `samples` times like the plain runner
+fn spawn_workers(days: &[u8], jobs: usize, samples: usize, options: RunOptions, tx: Sender<Event>) {
+    let days: Arc<[u8]> = days.into();
+    let next = Arc::new(AtomicUsize::new(0));
+    let options = Arc::new(options);
+
+    for _ in 0..jobs.min(days.len()) {
+        let (days, next, options, tx) = (days.clone(), next.clone(), options.clone(), tx.clone());
+
+        thread::spawn(move || {
+            while let Some(&day) = days.get(next.fetch_add(1, Ordering::Relaxed)) {
+                tx.send(Event::Running(day)).ok();
+                DAY.set(Some(day));
+
+                let mut progress = |v| {
+                    tx.send(Event::Progress(day, v)).ok();
+                };
+
+                let timing = panic::catch_unwind(AssertUnwindSafe(|| {
+                    let timing = crate::run(day, &options, &mut progress);
+
+                    match samples {
+                        1 => timing,
+                        _ => timing.map(|v| crate::sample(day, &options, v, samples)),
+                    }
+                }));
+
+                DAY.set(None);
+                tx.send(Event::Finished(day, timing.ok().flatten())).ok();
+            }
+        });
+    }
+}
+
+fn capture_logs(tx: &Sender<Event>, log_file: Option<&Path>) -> io::Result<()> {
+    let log_tx = tx.clone();
+    let mut dispatch = logger().chain(fern::Output::call(move |record| {
+        log_tx
+            .send(Event::Log(DAY.get(), record.args().to_string()))
+            .ok();
+    }));
+
+    if let Some(path) = log_file {
+        dispatch = dispatch.chain(fs::File::create(path)?);
+    }
+
+    dispatch.apply().map_err(io::Error::other)?;
+
+    let panic_tx = tx.clone();
+    panic::set_hook(Box::new(move |info| {
+        panic_tx.send(Event::Log(DAY.get(), info.to_string())).ok();
+    }));
+
+    Ok(())
+}
+
+fn event_loop(dashboard: &mut Dashboard, rx: &Receiver<Event>) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout());
+
+    loop {
+        while let Ok(event) = rx.try_recv() {
+            dashboard.apply(event);
+        }
+
+        dashboard.draw(&mut out)?;
+
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+
+        let TermEvent::Key(key) = event::read()? else {
+            continue;
+        };
+
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Left | KeyCode::Char('h') => dashboard.select(-1),
+            KeyCode::Right | KeyCode::Char('l') => dashboard.select(1),
+            KeyCode::Up | KeyCode::Char('k') => dashboard.select(-(COLUMNS as i8)),
+            KeyCode::Down | KeyCode::Char('j') => dashboard.select(COLUMNS as i8),
+            _ => {}
+        }
+    }
+}
+
+/// Runs the days of `args` behind the dashboard until the user quits it, with
+/// the timings and whether every day finished before that
+pub fn run(args: &Args, config: &Config) -> io::Result<(Vec<Option<Timing>>, bool)> {
+    let (tx, rx) = mpsc::channel();
+    let mut dashboard = Dashboard::new(&args.days, config.year);
+    let log_file = config.log.enabled.then_some(config.log.file.as_path());
+
+    capture_logs(&tx, log_file)?;
+
+    // after the logs are captured, so the warnings about the cache are shown
+    let options = RunOptions::for_days(args, config);
+
+    let guard = TerminalGuard::enter()?;
+    let samples = crate::samples(args, config);
+    spawn_workers(&args.days, crate::jobs(args), samples, options, tx);
+    let res = event_loop(&mut dashboard, &rx);
+    drop(guard);
+
+    let _ = panic::take_hook();
+    let done = dashboard.done();
+    res.map(|_| (dashboard.timings, done))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseError;
+
+    fn timing() -> Timing {
+        Timing {
+            title: "Title",
+            parsing: Duration::ZERO,
+            part1: Duration::ZERO,
+            part2: Duration::ZERO,
+            answers: ["1".to_owned(), "2".to_owned()],
+            allocations: Default::default(),
+            spans: Default::default(),
+            visualization: None,
+        }
+    }
+
+    #[test]
+    fn solved_day() {
+        let mut dashboard = Dashboard::new(&[2], 2022);
+        assert!(dashboard.days[2].status == Status::Missing);
+
+        dashboard.apply(Event::Running(2));
+        dashboard.apply(Event::Progress(2, Progress::Started("Title")));
+        assert!(dashboard.days[1].status == Status::Running);
+        assert_eq!(dashboard.selected, 2);
+
+        dashboard.apply(Event::Log(None, "a line".to_owned()));
+        dashboard.apply(Event::Progress(
+            2,
+            Progress::Parsed(Duration::from_millis(1)),
+        ));
+        let part1 = Progress::Solved(1, "1".to_owned(), Duration::from_millis(2));
+        dashboard.apply(Event::Progress(2, part1));
+        dashboard.apply(Event::Finished(2, Some(timing())));
+
+        let state = &dashboard.days[1];
+        assert!(state.status == Status::Solved);
+        assert_eq!(state.title, Some("Title"));
+        assert_eq!(state.logs, ["a line"]);
+        assert_eq!(
+            state.parts[0],
+            Some(("1".to_owned(), Duration::from_millis(2)))
+        );
+        assert_eq!(state.total(), Some(Duration::from_millis(3)));
+        assert!(dashboard.running.is_empty());
+        assert!(dashboard.done());
+        assert!(dashboard.timings[1].is_some());
+
+        // nothing is running, so the line is the runner's
+        dashboard.apply(Event::Log(None, "cache".to_owned()));
+        assert_eq!(dashboard.days[1].logs.len(), 1);
+        assert_eq!(dashboard.logs, ["cache"]);
+    }
+
+    #[test]
+    fn concurrent_days() {
+        let mut dashboard = Dashboard::new(&[1, 2], 2022);
+
+        dashboard.apply(Event::Running(1));
+        dashboard.apply(Event::Running(2));
+        dashboard.apply(Event::Log(Some(1), "first".to_owned()));
+        dashboard.apply(Event::Log(None, "unknown".to_owned()));
+        dashboard.apply(Event::Finished(2, Some(timing())));
+        assert!(!dashboard.done());
+
+        // the only day running gets the lines of unknown threads
+        dashboard.apply(Event::Log(None, "timeout".to_owned()));
+        dashboard.apply(Event::Finished(1, Some(timing())));
+        assert!(dashboard.done());
+
+        assert_eq!(dashboard.days[0].logs, ["first", "timeout"]);
+        assert!(dashboard.days[1].logs.is_empty());
+        assert_eq!(dashboard.logs, ["unknown"]);
+    }
+
+    #[test]
+    fn failed_days() {
+        let mut dashboard = Dashboard::new(&[1, 2, 3], 2022);
+
+        dashboard.apply(Event::Running(1));
+        let error = ParseError::new("bad").at(1, 2);
+        dashboard.apply(Event::Progress(1, Progress::ParseFailed(error)));
+        dashboard.apply(Event::Finished(1, None));
+        assert!(dashboard.days[0].status == Status::Failed);
+        assert_eq!(
+            dashboard.days[0].logs,
+            ["Cannot parse the input: line 1, column 2: bad"]
+        );
+
+        // finished without a reason is a panic
+        dashboard.apply(Event::Running(2));
+        dashboard.apply(Event::Finished(2, None));
+        assert!(dashboard.days[1].status == Status::Failed);
+
+        dashboard.apply(Event::Running(3));
+        dashboard.apply(Event::Progress(3, Progress::MissingInput));
+        dashboard.apply(Event::Finished(3, None));
+        assert!(dashboard.days[2].status == Status::Missing);
+
+        // days out of range are ignored
+        dashboard.apply(Event::Running(0));
+        dashboard.apply(Event::Progress(26, Progress::Cached));
+        dashboard.apply(Event::Finished(0, None));
+    }
+
+    #[test]
+    fn follow_selection() {
+        let mut dashboard = Dashboard::new(&[1, 2], 2022);
+        dashboard.follow = false;
+
+        dashboard.apply(Event::Progress(2, Progress::Started("Title")));
+        assert_eq!(dashboard.selected, 1);
+    }
+}
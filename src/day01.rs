@@ -1,51 +1,404 @@
-use std::mem;
+use crate::utils::parse::{lines, unsigned};
+use crate::utils::{stream_lines, top_k, ParseError, Section, Sections, TopK};
+use std::cmp::Reverse;
+use std::fmt::Display;
+use std::io::BufRead;
+
+/// The totals and answers, the `wide-calories` feature sums into a `u128`
+#[cfg(not(feature = "wide-calories"))]
+pub type Total = u64;
+#[cfg(feature = "wide-calories")]
+pub type Total = u128;
+
+/// An unsigned integer the calories are summed into
+pub trait Accumulator: Copy + Ord + Default + Display + From<u64> {
+    const NAME: &'static str;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn as_f64(self) -> f64;
+}
+
+macro_rules! accumulator {
+    ($($t:ident),*) => {$(
+        impl Accumulator for $t {
+            const NAME: &'static str = stringify!($t);
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                $t::checked_add(self, other)
+            }
+
+            fn as_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    )*};
+}
+
+accumulator!(u64, u128);
+
+/// The sum of `values`, `None` when it does not fit into `T`
+fn checked_sum<T: Accumulator>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    values
+        .into_iter()
+        .try_fold(T::default(), |sum, v| sum.checked_add(v))
+}
+
+/// The sum of a bag, or the index of the item that makes it overflow
+fn checked_total<T: Accumulator>(bag: &[u64]) -> Result<T, usize> {
+    bag.iter()
+        .enumerate()
+        .try_fold(T::default(), |sum, (idx, &v)| {
+            sum.checked_add(T::from(v)).ok_or(idx)
+        })
+}
+
+fn overflow(elf: usize) -> ParseError {
+    ParseError::new(format!(
+        "the calories of elf {elf} do not fit into a {}",
+        Total::NAME
+    ))
+}
+
+/// The answer of part 2, which can overflow even when every total fits
+fn top3_sum(totals: impl IntoIterator<Item = Total>) -> Result<Total, ParseError> {
+    checked_sum(top_k(totals, 3)).ok_or_else(|| {
+        ParseError::new(format!(
+            "the 3 largest totals do not fit into a {} together",
+            Total::NAME
+        ))
+    })
+}
 
 pub struct CalorieCounting {
     bags: Vec<Vec<u64>>,
+    /// The sum of every bag, checked for overflows while parsing
+    totals: Vec<Total>,
+}
+
+/// An elf of the ranking, `elf` is its 1-based position in the input
+#[derive(Debug, PartialEq)]
+struct Rank {
+    elf: usize,
+    items: usize,
+    calories: Total,
+    /// Part of the calories of every elf, from 0 to 1
+    share: f64,
+}
+
+/// The percentiles in `Summary::percentiles`
+const PERCENTILES: [f64; 5] = [25.0, 50.0, 75.0, 90.0, 99.0];
+
+/// The width of the histogram buckets when the `stats` query is not given one
+const BUCKET_WIDTH: u64 = 10000;
+
+/// The most buckets of a histogram, narrower buckets than that are refused
+const MAX_BUCKETS: usize = 1000;
+
+/// The spread of some values
+#[derive(Debug, PartialEq)]
+struct Summary<T> {
+    count: usize,
+    min: T,
+    max: T,
+    mean: f64,
+    /// Of the whole population, not of a sample
+    std_dev: f64,
+    /// The `PERCENTILES` of the values, the second one is the median
+    percentiles: [f64; 5],
+}
+
+impl<T: Accumulator> Summary<T> {
+    /// `None` when there are no values
+    fn new(mut values: Vec<T>) -> Option<Self> {
+        values.sort_unstable();
+
+        let (&min, &max) = (values.first()?, values.last()?);
+        let count = values.len();
+        let mean = values.iter().map(|v| v.as_f64()).sum::<f64>() / count as f64;
+        let variance = values
+            .iter()
+            .map(|v| (v.as_f64() - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+
+        Some(Self {
+            count,
+            min,
+            max,
+            mean,
+            std_dev: variance.sqrt(),
+            percentiles: PERCENTILES.map(|p| percentile(&values, p)),
+        })
+    }
+}
+
+/// The `p`th percentile of non-empty sorted values, interpolated between the closest ranks
+fn percentile<T: Accumulator>(sorted: &[T], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let low = sorted[rank.floor() as usize].as_f64();
+    let high = sorted[rank.ceil() as usize].as_f64();
+
+    low + (high - low) * rank.fract()
+}
+
+struct Stats {
+    /// Of the total calories of every elf
+    elves: Option<Summary<Total>>,
+    /// Of the calories of every single item
+    items: Option<Summary<u64>>,
+    /// The 1-based positions of the elves without any item
+    empty: Vec<usize>,
+}
+
+impl CalorieCounting {
+    fn totals(&self) -> impl Iterator<Item = Total> + '_ {
+        self.totals.iter().copied()
+    }
+
+    /// The `k` largest totals from the largest, every total when there are fewer elves
+    fn top_k(&self, k: usize) -> Vec<Total> {
+        top_k(self.totals(), k)
+    }
+
+    /// The `k` elves carrying the most calories, the first one in the input wins a tie
+    fn ranking(&self, k: usize) -> Vec<Rank> {
+        // the sum of every total could overflow, its ratios only need a float
+        let all: f64 = self.totals().map(|v| v as f64).sum();
+        let elves = self
+            .totals()
+            .zip(1..)
+            .map(|(calories, elf)| (calories, Reverse(elf)));
+
+        top_k(elves, k)
+            .into_iter()
+            .map(|(calories, Reverse(elf))| Rank {
+                elf,
+                items: self.bags[elf - 1].len(),
+                calories,
+                share: match all {
+                    0.0 => 0.0,
+                    all => calories as f64 / all,
+                },
+            })
+            .collect()
+    }
+
+    fn stats_report(&self, width: u64) -> Result<String, String> {
+        Ok(stats_report(&self.stats(), &self.histogram(width)?, width))
+    }
+
+    fn stats(&self) -> Stats {
+        Stats {
+            elves: Summary::new(self.totals().collect()),
+            items: Summary::new(self.bags.iter().flatten().copied().collect()),
+            empty: (1..)
+                .zip(&self.bags)
+                .filter(|(_, bag)| bag.is_empty())
+                .map(|(elf, _)| elf)
+                .collect(),
+        }
+    }
+
+    /// How many elves carry a total in each bucket of `width` calories, as
+    /// the start of the bucket and the count, from the lowest total to the highest.
+    /// An error when that takes more than `MAX_BUCKETS` buckets
+    fn histogram(&self, width: u64) -> Result<Vec<(Total, usize)>, String> {
+        let width = Total::from(width);
+        let totals: Vec<Total> = self.totals().collect();
+        let (Some(min), Some(max)) = (totals.iter().min(), totals.iter().max()) else {
+            return Ok(Vec::new());
+        };
+
+        let first = min / width;
+        let count = (max / width - first)
+            .checked_add(1)
+            .and_then(|v| usize::try_from(v).ok())
+            .filter(|&v| v <= MAX_BUCKETS)
+            .ok_or_else(|| {
+                format!("buckets of {width} calories make more than {MAX_BUCKETS} buckets")
+            })?;
+        let mut buckets = vec![0; count];
+
+        for total in totals.iter() {
+            buckets[(total / width - first) as usize] += 1;
+        }
+
+        Ok((first..).map(|v| v * width).zip(buckets).collect())
+    }
+}
+
+fn summary_row<T: Accumulator>(name: &str, summary: &Option<Summary<T>>) -> String {
+    let Some(v) = summary else {
+        return format!("{name:<6} {:>6}\n", 0);
+    };
+
+    let mut res = format!(
+        "{name:<6} {:>6} {:>8} {:>8} {:>10.1} {:>10.1}",
+        v.count, v.min, v.max, v.mean, v.std_dev
+    );
+
+    for p in v.percentiles {
+        res.push_str(&format!(" {p:>10.1}"));
+    }
+
+    res.push('\n');
+    res
+}
+
+fn stats_report(stats: &Stats, histogram: &[(Total, usize)], width: u64) -> String {
+    let mut res = format!(
+        "{:<6} {:>6} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+        "", "count", "min", "max", "mean", "std dev", "p25", "median", "p75", "p90", "p99"
+    );
+
+    res.push_str(&summary_row("elves", &stats.elves));
+    res.push_str(&summary_row("items", &stats.items));
+
+    let empty: Vec<String> = stats.empty.iter().map(|v| v.to_string()).collect();
+    match empty.is_empty() {
+        true => res.push_str("\nElves with an empty bag: none\n"),
+        false => res.push_str(&format!(
+            "\nElves with an empty bag: {}\n",
+            empty.join(", ")
+        )),
+    }
+
+    res.push_str(&format!(
+        "\nElves by total calories (buckets of {width}):\n"
+    ));
+    let most = histogram.iter().map(|v| v.1).max().unwrap_or_default();
+
+    for &(start, count) in histogram {
+        // the longest bar is 40 wide, any other non-empty bucket gets at least 1
+        let bar = (count * 40).div_ceil(most.max(1));
+
+        res.push_str(&format!(
+            "{start:>8} - {:<8} | {:<40} {count}\n",
+            start.saturating_add(Total::from(width) - 1),
+            "#".repeat(bar)
+        ));
+    }
+
+    res
+}
+
+/// A positive number argument of a query
+fn number_arg<T: std::str::FromStr + Default + PartialEq>(
+    query: &str,
+    arg: &str,
+) -> Result<T, String> {
+    match arg.parse() {
+        Ok(v) if v != T::default() => Ok(v),
+        _ => Err(format!("`{query}` expects a positive number, not `{arg}`")),
+    }
 }
 
-impl crate::AdventOfCode for CalorieCounting {
+fn ranking_report(ranking: &[Rank]) -> String {
+    let mut res = String::from("rank    elf  items   calories   share\n");
+
+    for (v, rank) in ranking.iter().zip(1..) {
+        res.push_str(&format!(
+            "{rank:>4} {:>6} {:>6} {:>10} {:>6.2}%\n",
+            v.elf,
+            v.items,
+            v.calories,
+            v.share * 100.0
+        ));
+    }
+
+    res
+}
+
+impl crate::AdventOfCode<'_, Total> for CalorieCounting {
     const TITLE: &'static str = "Calorie Counting";
     const DAY: u8 = 1;
+    const VERSION: u32 = 2;
+    const QUERIES: &'static [&'static str] = &["ranking", "stats"];
 
-    fn new(input: &str) -> Option<Self> {
+    fn new(input: &str) -> Result<Self, ParseError> {
         let mut bags = Vec::new();
-        let mut bag = Vec::new();
+        let mut totals = Vec::new();
 
-        for line in input.lines() {
-            if line.is_empty() {
-                bags.push(mem::take(&mut bag));
-                continue;
-            }
+        for (section, elf) in Sections::new(input).with_empty().zip(1..) {
+            let bag: Vec<u64> = section.parse(lines(unsigned))?;
+            // the line that overflows, like `stream` reports it
+            let total =
+                checked_total(&bag).map_err(|idx| overflow(elf).at(section.line + idx, 1))?;
 
-            let calorie = line.parse::<u64>().ok()?;
-            bag.push(calorie);
+            bags.push(bag);
+            totals.push(total);
         }
 
-        bags.push(bag);
+        top3_sum(totals.iter().copied())?;
 
-        Some(CalorieCounting { bags })
+        Ok(CalorieCounting { bags, totals })
     }
 
-    fn part1(&self) -> u64 {
-        self.bags
-            .iter()
-            .map(|v| v.iter().sum())
-            .max()
-            .unwrap_or_default()
+    fn part1(&self) -> Total {
+        self.top_k(1).into_iter().sum()
     }
 
-    fn part2(&self) -> u64 {
-        let mut top3 = [0; 3];
+    fn part2(&self) -> Total {
+        self.top_k(3).into_iter().sum()
+    }
+
+    /// `ranking [N]` lists the N elves carrying the most calories, or all of them,
+    /// `stats [WIDTH]` summarizes the bags with a histogram of buckets of WIDTH calories
+    fn query(input: &str, name: &str, args: &[String]) -> Result<String, String> {
+        let f = Self::new_query(input, Self::new)?;
+
+        match (name, args) {
+            ("ranking", []) => Ok(ranking_report(&f.ranking(f.bags.len()))),
+            ("ranking", [k]) => Ok(ranking_report(&f.ranking(number_arg(name, k)?))),
+            ("ranking", _) => Err("usage: ranking [N]".into()),
+            ("stats", []) => f.stats_report(BUCKET_WIDTH),
+            ("stats", [width]) => f.stats_report(number_arg(name, width)?),
+            _ => Err("usage: stats [WIDTH]".into()),
+        }
+    }
+}
+
+impl crate::Streaming<'_, Total> for CalorieCounting {
+    fn stream(reader: impl BufRead) -> Result<[Total; 2], ParseError> {
+        let mut top3 = TopK::new(3);
+        let mut bag: Option<Total> = None;
+        let mut elf = 1;
+        // empty bags, they only count when another bag follows, like with `Sections::with_empty`
+        let mut empty = 0;
+
+        for line in stream_lines(reader) {
+            let (number, line) = line?;
+
+            if line.is_empty() {
+                match bag.take() {
+                    Some(total) => {
+                        top3.push(total);
+                        elf += 1;
+                    }
+                    None => empty += 1,
+                }
+                continue;
+            }
 
-        for calories in self.bags.iter().map(|v| v.iter().sum()) {
-            if calories > top3[0] {
-                top3[0] = calories;
-                top3.sort_unstable();
+            for _ in 0..empty {
+                top3.push(Total::default());
             }
+            elf += std::mem::take(&mut empty);
+
+            let section = Section {
+                text: &line,
+                line: number,
+            };
+            let calories = Total::from(section.parse(unsigned::<u64>)?);
+            let total = bag.unwrap_or_default().checked_add(calories);
+
+            bag = Some(total.ok_or_else(|| overflow(elf).at(number, 1))?);
         }
 
-        top3.into_iter().sum()
+        top3.push(bag.unwrap_or_default());
+        let top3 = top3.into_sorted_vec();
+
+        Ok([top3[0], top3_sum(top3)?])
     }
 }
 
@@ -69,6 +422,12 @@ mod tests {
 
 10000"#;
 
+    #[test]
+    fn stream() {
+        let res = CalorieCounting::stream(EXAMPLE_INPUT.as_bytes());
+        assert_eq!(res, Ok([24000, 45000]));
+    }
+
     #[test]
     fn part1() {
         let res = CalorieCounting::new_unwrap(EXAMPLE_INPUT);
@@ -80,4 +439,166 @@ mod tests {
         let res = CalorieCounting::new_unwrap(EXAMPLE_INPUT);
         assert_eq!(res.part2(), 45000);
     }
+
+    #[test]
+    fn top_k() {
+        let res = CalorieCounting::new_unwrap(EXAMPLE_INPUT);
+        assert_eq!(res.top_k(2), [24000, 11000]);
+        assert_eq!(res.top_k(9), [24000, 11000, 10000, 6000, 4000]);
+    }
+
+    #[test]
+    fn ranking() {
+        let res = CalorieCounting::new_unwrap(EXAMPLE_INPUT);
+        let ranking = res.ranking(2);
+
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(
+            (ranking[0].elf, ranking[0].items, ranking[0].calories),
+            (4, 3, 24000)
+        );
+        assert_eq!(
+            (ranking[1].elf, ranking[1].items, ranking[1].calories),
+            (3, 2, 11000)
+        );
+        assert!((ranking[0].share - 24000.0 / 55000.0).abs() < 1e-9);
+        assert_eq!(res.ranking(9).len(), 5);
+    }
+
+    #[test]
+    fn stats() {
+        let res = CalorieCounting::new_unwrap(EXAMPLE_INPUT);
+        let stats = res.stats();
+
+        let elves = stats.elves.unwrap();
+        assert_eq!((elves.count, elves.min, elves.max), (5, 4000, 24000));
+        assert_eq!((elves.mean, elves.percentiles[1]), (11000.0, 10000.0));
+        assert_eq!(elves.percentiles[..4], [6000.0, 10000.0, 11000.0, 18800.0]);
+        assert!((elves.std_dev - 48_800_000f64.sqrt()).abs() < 1e-9);
+
+        let items = stats.items.unwrap();
+        assert_eq!(
+            (items.count, items.mean, items.percentiles[1]),
+            (10, 5500.0, 5500.0)
+        );
+        assert!(stats.empty.is_empty());
+
+        let res = CalorieCounting::new_unwrap("1\n\n\n\n2");
+        assert_eq!(res.stats().empty, [2, 3]);
+
+        let res = CalorieCounting::new_unwrap("\n3\n\n\n4\n\n\n");
+        assert_eq!(res.bags, [vec![], vec![3], vec![], vec![4]]);
+        assert_eq!(res.stats().empty, [1, 3]);
+
+        let res = CalorieCounting {
+            bags: vec![],
+            totals: vec![],
+        };
+        assert!(res.stats().elves.is_none());
+    }
+
+    #[test]
+    fn histogram() {
+        let res = CalorieCounting::new_unwrap(EXAMPLE_INPUT);
+        assert_eq!(
+            res.histogram(5000),
+            Ok(vec![(0, 1), (5000, 1), (10000, 2), (15000, 0), (20000, 1)])
+        );
+        assert_eq!(res.histogram(100000), Ok(vec![(0, 5)]));
+        assert!(res.histogram(20).is_err());
+
+        let res = CalorieCounting::new_unwrap(&format!("0\n\n{}", u64::MAX));
+        assert!(res.histogram(1).is_err());
+        assert_eq!(res.histogram(u64::MAX).map(|v| v.len()), Ok(2));
+    }
+
+    #[test]
+    fn checked_near_max() {
+        let near_max = (u64::MAX - 16..=u64::MAX).chain([u64::MAX / 2, u64::MAX / 2 + 1]);
+
+        for a in near_max {
+            for b in (0..=16).chain([u64::MAX / 2, u64::MAX - a, u64::MAX - a + 1]) {
+                let bag = [a, b];
+                let wide = a as u128 + b as u128;
+
+                assert_eq!(checked_total::<u64>(&bag).ok(), a.checked_add(b));
+                assert_eq!(checked_total::<u128>(&bag), Ok(wide));
+                assert_eq!(checked_total::<u64>(&bag).is_ok(), wide <= u64::MAX as u128);
+            }
+        }
+
+        assert_eq!(checked_total::<u64>(&[u64::MAX; 3]), Err(1));
+        assert_eq!(
+            checked_total::<u128>(&[u64::MAX; 3]),
+            Ok(3 * u64::MAX as u128)
+        );
+        assert_eq!(checked_total::<u64>(&[]), Ok(0));
+    }
+
+    #[cfg(not(feature = "wide-calories"))]
+    #[test]
+    fn empty_bags() {
+        let input = format!("\n\n5\n\n\n{}\n1\n\n", u64::MAX);
+        let message = "the calories of elf 5 do not fit into a u64";
+
+        assert_eq!(CalorieCounting::new(&input).err().unwrap().message, message);
+        assert_eq!(
+            CalorieCounting::stream(input.as_bytes())
+                .unwrap_err()
+                .message,
+            message
+        );
+
+        let input = "\n7\n\n\n\n2\n\n";
+        let res = CalorieCounting::new_unwrap(input);
+        assert_eq!(res.totals, [0, 7, 0, 0, 2]);
+        assert_eq!(
+            CalorieCounting::stream(input.as_bytes()),
+            Ok([res.part1(), res.part2()])
+        );
+    }
+
+    #[test]
+    fn too_large_item() {
+        let input = format!("1\n\n{}0", u64::MAX);
+        let e = CalorieCounting::new(&input).err().unwrap();
+        assert_eq!(e.position, Some((3, 1)));
+    }
+
+    #[cfg(not(feature = "wide-calories"))]
+    #[test]
+    fn overflow() {
+        let max = u64::MAX;
+        let input = format!("1\n\n{max}\n0\n\n{max}\n1\n");
+        let expected = "line 7, column 1: the calories of elf 3 do not fit into a u64";
+        let e = CalorieCounting::new(&input).err().unwrap();
+        assert_eq!(e.to_string(), expected);
+
+        let e = CalorieCounting::stream(input.as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), expected);
+
+        let input = format!("{max}\n\n1");
+        assert!(CalorieCounting::new(&input).is_err());
+        assert!(CalorieCounting::stream(input.as_bytes()).is_err());
+
+        let input = format!("{max}");
+        let res = CalorieCounting::new_unwrap(&input);
+        assert_eq!((res.part1(), res.part2()), (max, max));
+    }
+
+    #[cfg(feature = "wide-calories")]
+    #[test]
+    fn wide() {
+        let max = u64::MAX;
+        let input = format!("1\n\n{max}\n0\n\n{max}\n1\n\n{max}");
+        let res = CalorieCounting::new_unwrap(&input);
+        let max = max as u128;
+
+        assert_eq!(res.part1(), max + 1);
+        assert_eq!(res.part2(), 3 * max + 1);
+        assert_eq!(
+            CalorieCounting::stream(input.as_bytes()),
+            Ok([max + 1, 3 * max + 1])
+        );
+    }
 }
@@ -1,65 +1,684 @@
-pub struct RockPaperScissors {
-    instructions: Vec<(char, char)>,
+use crate::utils::parse::{self, any_char, lines, literal, unsigned, word, ParseResult, Parser};
+use crate::utils::{stream_lines, ParseError, Section};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::BufRead;
+
+/// The rules of the puzzle
+const RPS: &str = include_str!("../rules/rps.txt");
+/// Rock Paper Scissors Lizard Spock
+const RPSLS: &str = include_str!("../rules/rpsls.txt");
+
+/// `analyze` tries every reading of the response symbols, n! of them as shapes
+/// and about 3^n as outcomes, so it stops at this many shapes
+const MAX_ANALYZED_SHAPES: usize = 7;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Outcome {
+    Loss,
+    Draw,
+    Win,
 }
 
-impl crate::AdventOfCode for RockPaperScissors {
-    const TITLE: &'static str = "Rock Paper Scissors";
-    const DAY: u8 = 2;
+impl Outcome {
+    fn parse(input: &str) -> ParseResult<'_, Self> {
+        let outcome = |name| match name {
+            "loss" => Some(Self::Loss),
+            "draw" => Some(Self::Draw),
+            "win" => Some(Self::Win),
+            _ => None,
+        };
 
-    fn new(input: &str) -> Option<Self> {
-        let mut instructions = Vec::new();
+        word.try_map("loss, draw or win", outcome).parse(input)
+    }
 
-        for line in input.lines() {
-            let mut iter = line.chars();
-            let first_column = iter.next()?;
-            let second_column = iter.nth(1)?;
-            instructions.push((first_column, second_column));
+    const ALL: [Self; 3] = [Self::Loss, Self::Draw, Self::Win];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Loss => "loss",
+            Self::Draw => "draw",
+            Self::Win => "win",
         }
+    }
 
-        Some(RockPaperScissors { instructions })
+    fn score(self) -> u64 {
+        match self {
+            Self::Loss => 0,
+            Self::Draw => 3,
+            Self::Win => 6,
+        }
     }
+}
 
-    fn part1(&self) -> u64 {
-        let mut score = 0;
-
-        for instruction in &self.instructions {
-            score += match instruction.1 {
-                'X' => 1,
-                'Y' => 2,
-                'Z' => 3,
-                _ => 0,
+#[derive(Debug)]
+struct Shape {
+    name: String,
+    /// How the first column of the strategy guide writes it
+    opponent: char,
+    /// How the second column writes it, when it is read as a shape
+    response: char,
+    score: u64,
+}
+
+/// A character that is not whitespace, after a space
+fn symbol(input: &str) -> ParseResult<'_, char> {
+    let symbol = any_char.try_map("a symbol", |v| (!v.is_whitespace()).then_some(v));
+    literal(" ").then(symbol).parse(input)
+}
+
+/// A line of a rules file
+enum Statement<'a> {
+    /// `shape <name> <opponent's symbol> <response symbol> <score>`
+    Shape(Shape),
+    /// `outcome <loss|draw|win> <response symbol>...`
+    Outcome(Outcome, Vec<char>),
+    /// `<shape> beats <shape>...`
+    Beats(&'a str, Vec<&'a str>),
+}
+
+impl<'a> Statement<'a> {
+    fn parse(input: &'a str) -> ParseResult<'a, Self> {
+        let shape = literal("shape ")
+            .then(word)
+            .and(symbol)
+            .and(symbol)
+            .skip(literal(" "))
+            .and(unsigned)
+            .map(|(((name, opponent), response), score)| {
+                Self::Shape(Shape {
+                    name: name.to_owned(),
+                    opponent,
+                    response,
+                    score,
+                })
+            });
+        let outcome = literal("outcome ")
+            .then(Outcome::parse)
+            .and(symbol.many())
+            .map(|(outcome, symbols)| Self::Outcome(outcome, symbols));
+        let beats = word
+            .skip(literal(" beats"))
+            .and(literal(" ").then(word).many())
+            .map(|(winner, losers)| Self::Beats(winner, losers));
+
+        shape.or(outcome).or(beats).parse(input)
+    }
+}
+
+/// The shapes of a game and which one wins against which
+#[derive(Debug)]
+struct Rules {
+    shapes: Vec<Shape>,
+    /// `beats[a][b]` when shape `a` wins against shape `b`
+    beats: Vec<Vec<bool>>,
+    /// What the response symbols mean in part 2
+    outcomes: HashMap<char, Outcome>,
+}
+
+impl Rules {
+    fn classic() -> Self {
+        Self::parse(RPS).expect("the built-in rules are valid")
+    }
+
+    /// The built-in `rps` or `rpsls` rules, or the rules file at `name`
+    fn load(name: &str) -> Result<Self, String> {
+        let text = match name {
+            "rps" => RPS.to_owned(),
+            "rpsls" => RPSLS.to_owned(),
+            path => fs::read_to_string(path).map_err(|e| format!("cannot read `{path}`: {e}"))?,
+        };
+
+        Self::parse(&text).map_err(|e| format!("invalid rules `{name}`: {e}"))
+    }
+
+    /// Blank lines and lines starting with `#` are skipped, the shapes can be
+    /// declared after the lines that use them
+    fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut shapes: Vec<Shape> = Vec::new();
+        let mut outcomes = HashMap::new();
+        let mut relations = Vec::new();
+
+        for (line, number) in text.lines().zip(1..) {
+            let line = line.trim_end();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let section = Section {
+                text: line,
+                line: number,
             };
 
-            score += match instruction {
-                ('A', 'Y') | ('B', 'Z') | ('C', 'X') => 6,
-                ('A', 'Z') | ('B', 'X') | ('C', 'Y') => 0,
-                _ => 3,
+            match section.parse(Statement::parse)? {
+                Statement::Shape(shape) => {
+                    let clash = shapes.iter().find(|v| {
+                        v.name == shape.name
+                            || v.opponent == shape.opponent
+                            || v.response == shape.response
+                    });
+
+                    if let Some(other) = clash {
+                        let message = format!(
+                            "`{}` reuses a name or symbol of `{}`",
+                            shape.name, other.name
+                        );
+                        return Err(ParseError::new(message).at(number, 1));
+                    }
+
+                    shapes.push(shape);
+                }
+                Statement::Outcome(outcome, symbols) => {
+                    for symbol in symbols {
+                        if outcomes.insert(symbol, outcome).is_some() {
+                            let message = format!("`{symbol}` has more than one outcome");
+                            return Err(ParseError::new(message).at(number, 1));
+                        }
+                    }
+                }
+                Statement::Beats(winner, losers) => relations.push((number, winner, losers)),
+            }
+        }
+
+        let index = |name: &str, number: usize| {
+            shapes
+                .iter()
+                .position(|v| v.name == name)
+                .ok_or_else(|| ParseError::new(format!("unknown shape `{name}`")).at(number, 1))
+        };
+
+        let mut beats = vec![vec![false; shapes.len()]; shapes.len()];
+
+        for (number, winner, losers) in relations {
+            let winner = index(winner, number)?;
+
+            for loser in losers {
+                let loser = index(loser, number)?;
+
+                if winner == loser || beats[loser][winner] {
+                    let message = format!(
+                        "`{}` and `{loser_name}` cannot beat each other",
+                        shapes[winner].name,
+                        loser_name = shapes[loser].name
+                    );
+                    return Err(ParseError::new(message).at(number, 1));
+                }
+
+                beats[winner][loser] = true;
+            }
+        }
+
+        let rules = Self {
+            shapes,
+            beats,
+            outcomes,
+        };
+        rules.validate()?;
+
+        Ok(rules)
+    }
+
+    /// Makes sure that both parts can score any round: every two shapes have
+    /// exactly one winner (a tournament), every response has a shape and an
+    /// outcome, and every outcome can be reached against every shape
+    fn validate(&self) -> Result<(), ParseError> {
+        let error = |message: String| Err(ParseError::new(message));
+
+        if self.shapes.is_empty() {
+            return error("there are no shapes".into());
+        }
+
+        for (a, first) in self.shapes.iter().enumerate() {
+            for (b, second) in self.shapes.iter().enumerate().skip(a + 1) {
+                if !self.beats[a][b] && !self.beats[b][a] {
+                    return error(format!(
+                        "no winner between `{}` and `{}`",
+                        first.name, second.name
+                    ));
+                }
+            }
+
+            if !self.outcomes.contains_key(&first.response) {
+                return error(format!("the response `{}` has no outcome", first.response));
+            }
+        }
+
+        for (&symbol, &outcome) in &self.outcomes {
+            if self.response(symbol).is_none() {
+                return error(format!("`{symbol}` is not the response of a shape"));
+            }
+
+            for theirs in 0..self.shapes.len() {
+                if self.shape_against(theirs, outcome).is_none() {
+                    return error(format!(
+                        "no shape has the outcome {} against `{}`",
+                        outcome.name(),
+                        self.shapes[theirs].name
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn opponent(&self, symbol: char) -> Option<usize> {
+        self.shapes.iter().position(|v| v.opponent == symbol)
+    }
+
+    fn response(&self, symbol: char) -> Option<usize> {
+        self.shapes.iter().position(|v| v.response == symbol)
+    }
+
+    /// The outcome of playing the shape `mine` against the shape `theirs`
+    fn outcome(&self, mine: usize, theirs: usize) -> Outcome {
+        if self.beats[mine][theirs] {
+            Outcome::Win
+        } else if self.beats[theirs][mine] {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    /// The highest scoring shape to play against `theirs` for this outcome
+    fn shape_against(&self, theirs: usize, outcome: Outcome) -> Option<usize> {
+        (0..self.shapes.len())
+            .filter(|&mine| self.outcome(mine, theirs) == outcome)
+            .max_by_key(|&mine| self.shapes[mine].score)
+    }
+
+    fn score(&self, mine: usize, theirs: usize) -> u64 {
+        self.shapes[mine].score + self.outcome(mine, theirs).score()
+    }
+}
+
+/// A line of the strategy guide, with the shapes as indices into the rules
+#[derive(Clone, Copy)]
+struct Round {
+    theirs: usize,
+    /// The second column, as the index of the shape that writes it, part 1 plays that shape
+    response: usize,
+    /// The second column read as an outcome, for part 2
+    outcome: Outcome,
+}
+
+impl Round {
+    fn parse<'a>(rules: &Rules, input: &'a str) -> ParseResult<'a, Self> {
+        let theirs = any_char.try_map("an opponent's shape", |v| rules.opponent(v));
+        let response = any_char.try_map("a response", |v| {
+            Some((rules.response(v)?, *rules.outcomes.get(&v)?))
+        });
+
+        theirs
+            .skip(literal(" "))
+            .and(response)
+            .map(|(theirs, (response, outcome))| Self {
+                theirs,
+                response,
+                outcome,
+            })
+            .parse(input)
+    }
+
+    /// The response is the shape to play
+    fn score1(self, rules: &Rules) -> u64 {
+        rules.score(self.response, self.theirs)
+    }
+
+    /// The response is the outcome of the round
+    fn score2(self, rules: &Rules) -> u64 {
+        let mine = rules
+            .shape_against(self.theirs, self.outcome)
+            .expect("the rules were validated");
+        rules.score(mine, self.theirs)
+    }
+}
+
+/// A meaning of the second column, indexed like the response symbols in `Rules::shapes`
+#[derive(Clone, Debug, PartialEq)]
+enum Meaning {
+    Shapes(Vec<usize>),
+    Outcomes(Vec<Outcome>),
+}
+
+/// The scores of the strategy guide under a meaning of the second column
+#[derive(Debug)]
+struct Analysis {
+    meaning: Meaning,
+    total: u64,
+    /// How many rounds have each score
+    distribution: BTreeMap<u64, usize>,
+}
+
+/// How a round of the strategy guide scores under one of the parts
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Step {
+    /// The line of the round in the input, 1-based
+    line: usize,
+    part: u8,
+    theirs: usize,
+    mine: usize,
+    outcome: Outcome,
+    /// The score of the shape played
+    shape_score: u64,
+    /// The score of the outcome of the round
+    outcome_score: u64,
+    /// The score of this round and of every round before it, in this part
+    total: u64,
+}
+
+/// Every order of `0..n`
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+
+    let mut res = Vec::new();
+
+    for shorter in permutations(n - 1) {
+        for idx in 0..n {
+            let mut permutation = shorter.clone();
+            permutation.insert(idx, n - 1);
+            res.push(permutation);
+        }
+    }
+
+    res
+}
+
+/// Every way to give `n` symbols an outcome that uses each outcome at least once
+fn outcome_assignments(n: usize) -> Vec<Vec<Outcome>> {
+    let mut res = vec![Vec::new()];
+
+    for _ in 0..n {
+        res = res
+            .into_iter()
+            .flat_map(|v: Vec<Outcome>| {
+                Outcome::ALL.map(|outcome| v.iter().copied().chain([outcome]).collect())
+            })
+            .collect();
+    }
+
+    res.retain(|v| Outcome::ALL.iter().all(|outcome| v.contains(outcome)));
+    res
+}
+
+/// A CSV field, quoted when a shape name has a separator, a quote or a line break
+fn csv_field(field: &str) -> Cow<'_, str> {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => Cow::Owned(format!("\"{}\"", field.replace('"', "\"\""))),
+        false => Cow::Borrowed(field),
+    }
+}
+
+pub struct RockPaperScissors {
+    rules: Rules,
+    rounds: Vec<Round>,
+}
+
+impl RockPaperScissors {
+    /// Reads the strategy guide with the symbols of `rules`
+    fn with_rules(input: &str, rules: Rules) -> Result<Self, ParseError> {
+        let rounds = parse::all(input, lines(|v| Round::parse(&rules, v)))?;
+        Ok(Self { rules, rounds })
+    }
+
+    /// The score of a round under a meaning of its second column `response`, `None`
+    /// when no shape has the wanted outcome against the opponent
+    fn score(&self, theirs: usize, response: usize, meaning: &Meaning) -> Option<u64> {
+        let mine = match meaning {
+            Meaning::Shapes(shapes) => shapes[response],
+            Meaning::Outcomes(outcomes) => self.rules.shape_against(theirs, outcomes[response])?,
+        };
+
+        Some(self.rules.score(mine, theirs))
+    }
+
+    /// The scores of every mapping of the response symbols to shapes, then of
+    /// every mapping to outcomes, each from the highest total. A mapping to outcomes
+    /// that some round of the guide cannot be played with is left out
+    fn analyze(&self) -> Result<[Vec<Analysis>; 2], String> {
+        let n = self.rules.shapes.len();
+        if n > MAX_ANALYZED_SHAPES {
+            return Err(format!(
+                "analyze reads rules with at most {MAX_ANALYZED_SHAPES} shapes, not {n}"
+            ));
+        }
+
+        // the score of a round depends only on its two columns
+        let mut counts = HashMap::new();
+        for round in &self.rounds {
+            *counts.entry((round.theirs, round.response)).or_insert(0) += 1;
+        }
+
+        let analysis = |meaning: Meaning| {
+            let mut distribution = BTreeMap::new();
+
+            for (&(theirs, response), &count) in &counts {
+                *distribution
+                    .entry(self.score(theirs, response, &meaning)?)
+                    .or_insert(0) += count;
+            }
+
+            Some(Analysis {
+                total: distribution
+                    .iter()
+                    .map(|(&score, &v)| score * v as u64)
+                    .sum(),
+                meaning,
+                distribution,
+            })
+        };
+
+        let mut shapes: Vec<Analysis> = permutations(n)
+            .into_iter()
+            .filter_map(|v| analysis(Meaning::Shapes(v)))
+            .collect();
+        let mut outcomes: Vec<Analysis> = outcome_assignments(n)
+            .into_iter()
+            .filter_map(|v| analysis(Meaning::Outcomes(v)))
+            .collect();
+
+        shapes.sort_by_key(|v| Reverse(v.total));
+        outcomes.sort_by_key(|v| Reverse(v.total));
+
+        Ok([shapes, outcomes])
+    }
+
+    fn describe(&self, meaning: &Meaning) -> String {
+        let symbols = self.rules.shapes.iter().map(|v| v.response);
+        let names: Vec<String> = match meaning {
+            Meaning::Shapes(shapes) => symbols
+                .zip(shapes)
+                .map(|(symbol, &v)| format!("{symbol}={}", self.rules.shapes[v].name))
+                .collect(),
+            Meaning::Outcomes(outcomes) => symbols
+                .zip(outcomes)
+                .map(|(symbol, v)| format!("{symbol}={}", v.name()))
+                .collect(),
+        };
+
+        names.join(" ")
+    }
+
+    /// Both parts round by round, the two steps of a round follow each other
+    fn trace(&self) -> Vec<Step> {
+        let mut totals = [0; 2];
+        let mut res = Vec::new();
+
+        for (round, line) in self.rounds.iter().zip(1..) {
+            for part in [1, 2] {
+                let mine = match part {
+                    1 => round.response,
+                    _ => self
+                        .rules
+                        .shape_against(round.theirs, round.outcome)
+                        .expect("the rules were validated"),
+                };
+                let outcome = self.rules.outcome(mine, round.theirs);
+                let shape_score = self.rules.shapes[mine].score;
+                let total = &mut totals[part as usize - 1];
+                *total += shape_score + outcome.score();
+
+                res.push(Step {
+                    line,
+                    part,
+                    theirs: round.theirs,
+                    mine,
+                    outcome,
+                    shape_score,
+                    outcome_score: outcome.score(),
+                    total: *total,
+                });
+            }
+        }
+
+        res
+    }
+
+    fn trace_report(&self, csv: bool) -> String {
+        let mut res = match csv {
+            true => "line,part,opponent,mine,outcome,shape_score,outcome_score,total\n".to_owned(),
+            false => format!(
+                "{:>6} {:>4}  {:<10} {:<10} {:<7} {:>9} {:>11} {:>8}\n",
+                "line", "part", "opponent", "mine", "outcome", "shape pts", "outcome pts", "total"
+            ),
+        };
+
+        for v in self.trace() {
+            let (theirs, mine) = (
+                &self.rules.shapes[v.theirs].name,
+                &self.rules.shapes[v.mine].name,
+            );
+            let outcome = v.outcome.name();
+
+            res.push_str(&match csv {
+                true => format!(
+                    "{},{},{},{},{outcome},{},{},{}\n",
+                    v.line,
+                    v.part,
+                    csv_field(theirs),
+                    csv_field(mine),
+                    v.shape_score,
+                    v.outcome_score,
+                    v.total
+                ),
+                false => format!(
+                    "{:>6} {:>4}  {theirs:<10} {mine:<10} {outcome:<7} {:>9} {:>11} {:>8}\n",
+                    v.line, v.part, v.shape_score, v.outcome_score, v.total
+                ),
+            });
+        }
+
+        res
+    }
+
+    fn analysis_report(&self) -> Result<String, String> {
+        let mut res = String::new();
+
+        for (list, title) in self.analyze()?.iter().zip(["shapes", "outcomes"]) {
+            let (Some(best), Some(worst)) = (list.first(), list.last()) else {
+                continue;
             };
+
+            res.push_str(&format!("The response symbols as {title}:\n"));
+
+            for v in list {
+                let distribution: Vec<String> = v
+                    .distribution
+                    .iter()
+                    .map(|(score, rounds)| format!("{score}x{rounds}"))
+                    .collect();
+
+                res.push_str(&format!(
+                    "  {:<40} {:>8}  {}\n",
+                    self.describe(&v.meaning),
+                    v.total,
+                    distribution.join(" ")
+                ));
+            }
+
+            res.push_str(&format!(
+                "Best:  {} ({})\n",
+                self.describe(&best.meaning),
+                best.total
+            ));
+            res.push_str(&format!(
+                "Worst: {} ({})\n\n",
+                self.describe(&worst.meaning),
+                worst.total
+            ));
         }
 
-        score
+        Ok(res)
+    }
+}
+
+impl crate::AdventOfCode<'_> for RockPaperScissors {
+    const TITLE: &'static str = "Rock Paper Scissors";
+    const DAY: u8 = 2;
+    const VERSION: u32 = 2;
+    const QUERIES: &'static [&'static str] = &["play", "analyze", "trace"];
+
+    fn new(input: &str) -> Result<Self, ParseError> {
+        Self::with_rules(input, Rules::classic())
+    }
+
+    fn part1(&self) -> u64 {
+        self.rounds.iter().map(|v| v.score1(&self.rules)).sum()
     }
 
     fn part2(&self) -> u64 {
-        let mut score = 0;
-
-        for instruction in &self.instructions {
-            score += match instruction.1 {
-                'X' => 0,
-                'Y' => 3,
-                'Z' => 6,
-                _ => 0,
-            };
+        self.rounds.iter().map(|v| v.score2(&self.rules)).sum()
+    }
+
+    /// `play <RULES>` solves both parts with the `rps` or `rpsls` rules, or a rules file,
+    /// `analyze [RULES]` scores every meaning the second column could have,
+    /// `trace [--csv] [RULES]` lists how every round scores in both parts
+    fn query(input: &str, name: &str, args: &[String]) -> Result<String, String> {
+        let csv = name == "trace" && args.first().is_some_and(|v| v == "--csv");
+        let args = &args[csv as usize..];
 
-            score += match instruction {
-                ('A', 'Y') | ('B', 'X') | ('C', 'Z') => 1,
-                ('A', 'Z') | ('B', 'Y') | ('C', 'X') => 2,
-                ('A', 'X') | ('B', 'Z') | ('C', 'Y') => 3,
-                _ => 0,
+        let rules = match (name, args) {
+            ("play", [rules]) | ("analyze" | "trace", [rules]) => Rules::load(rules)?,
+            ("analyze" | "trace", []) => Rules::classic(),
+            ("play", _) => return Err("usage: play <rps|rpsls|PATH>".into()),
+            ("analyze", _) => return Err("usage: analyze [rps|rpsls|PATH]".into()),
+            _ => return Err("usage: trace [--csv] [rps|rpsls|PATH]".into()),
+        };
+
+        let f = Self::new_query(input, |v| Self::with_rules(v, rules))?;
+
+        match name {
+            "play" => Ok(format!("Part 1: {}\nPart 2: {}\n", f.part1(), f.part2())),
+            "analyze" => f.analysis_report(),
+            _ => Ok(f.trace_report(csv)),
+        }
+    }
+}
+
+impl crate::Streaming<'_> for RockPaperScissors {
+    fn stream(reader: impl BufRead) -> Result<[u64; 2], ParseError> {
+        let rules = Rules::classic();
+        let mut scores = [0; 2];
+
+        for line in stream_lines(reader) {
+            let (number, line) = line?;
+            let section = Section {
+                text: &line,
+                line: number,
             };
+            let round = section.parse(|v| Round::parse(&rules, v))?;
+
+            scores[0] += round.score1(&rules);
+            scores[1] += round.score2(&rules);
         }
 
-        score
+        Ok(scores)
     }
 }
 
@@ -70,6 +689,12 @@ mod tests {
 
     const EXAMPLE_INPUT: &str = "A Y\nB X\nC Z";
 
+    #[test]
+    fn stream() {
+        let res = RockPaperScissors::stream(EXAMPLE_INPUT.as_bytes());
+        assert_eq!(res, Ok([15, 12]));
+    }
+
     #[test]
     fn part1() {
         let res = RockPaperScissors::new_unwrap(EXAMPLE_INPUT);
@@ -81,4 +706,202 @@ mod tests {
         let res = RockPaperScissors::new_unwrap(EXAMPLE_INPUT);
         assert_eq!(res.part2(), 12);
     }
+
+    #[test]
+    fn relations() {
+        for rules in [RPS, RPSLS].map(|v| Rules::parse(v).unwrap()) {
+            for theirs in 0..rules.shapes.len() {
+                assert_eq!(rules.outcome(theirs, theirs), Outcome::Draw);
+
+                for mine in (0..rules.shapes.len()).filter(|&v| v != theirs) {
+                    let outcome = rules.outcome(mine, theirs);
+                    assert_ne!(outcome, Outcome::Draw);
+                    assert_ne!(outcome, rules.outcome(theirs, mine));
+                }
+
+                for outcome in [Outcome::Loss, Outcome::Draw, Outcome::Win] {
+                    let mine = rules.shape_against(theirs, outcome).unwrap();
+                    assert_eq!(rules.outcome(mine, theirs), outcome);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rpsls() {
+        let input = "A V\nE W\nD Y";
+        let res = RockPaperScissors::with_rules(input, Rules::load("rpsls").unwrap()).unwrap();
+        assert_eq!((res.part1(), res.part2()), (14, 21));
+
+        assert!(RockPaperScissors::new(input).is_err());
+    }
+
+    #[test]
+    fn analyze() {
+        let res = RockPaperScissors::new_unwrap(EXAMPLE_INPUT);
+        let [shapes, outcomes] = res.analyze().unwrap();
+        let best_and_worst = |list: &[Analysis]| {
+            let (best, worst) = (list.first().unwrap(), list.last().unwrap());
+            [
+                (best.meaning.clone(), best.total),
+                (worst.meaning.clone(), worst.total),
+            ]
+        };
+
+        assert_eq!((shapes.len(), outcomes.len()), (6, 6));
+        assert_eq!(
+            best_and_worst(&shapes),
+            [
+                (Meaning::Shapes(vec![2, 1, 0]), 24),
+                (Meaning::Shapes(vec![0, 2, 1]), 6)
+            ]
+        );
+        assert_eq!(
+            best_and_worst(&outcomes),
+            [
+                (
+                    Meaning::Outcomes(vec![Outcome::Win, Outcome::Loss, Outcome::Draw]),
+                    18
+                ),
+                (
+                    Meaning::Outcomes(vec![Outcome::Loss, Outcome::Draw, Outcome::Win]),
+                    12
+                )
+            ]
+        );
+
+        let classic = Meaning::Shapes(vec![0, 1, 2]);
+        let classic = shapes.iter().find(|v| v.meaning == classic).unwrap();
+        assert_eq!(res.describe(&classic.meaning), "X=Rock Y=Paper Z=Scissors");
+        assert_eq!(classic.total, res.part1());
+        assert_eq!(
+            classic.distribution,
+            BTreeMap::from([(1, 1), (6, 1), (8, 1)])
+        );
+
+        assert_eq!(permutations(5).len(), 120);
+        assert_eq!(outcome_assignments(5).len(), 150);
+    }
+
+    #[test]
+    fn analyze_unreachable_outcomes() {
+        // nothing beats the Well, so every reading with a win is left out
+        let rules = "shape Well A W 4\nshape Paper B P 2\nshape Quill C Q 3\nshape Rock D R 1\n\
+            outcome loss W P\noutcome draw Q R\n\
+            Well beats Paper Quill Rock\nPaper beats Quill\nQuill beats Rock\nRock beats Paper";
+        let rules = Rules::parse(rules).unwrap();
+        let res = RockPaperScissors::with_rules("A W\nB Q\nD R", rules).unwrap();
+        let [shapes, outcomes] = res.analyze().unwrap();
+
+        assert_eq!((shapes.len(), outcomes.len()), (24, 24));
+        assert!(outcomes
+            .iter()
+            .all(|v| !matches!(&v.meaning, Meaning::Outcomes(o) if o[0] == Outcome::Win)));
+
+        let res = RockPaperScissors::with_rules("B Q\nD R", res.rules).unwrap();
+        assert_eq!(res.analyze().unwrap()[1].len(), 36);
+    }
+
+    #[test]
+    fn analyze_too_many_shapes() {
+        // every shape beats the next four, the smallest tournament above the limit
+        let mut rules = String::from("outcome loss a b c\noutcome draw d e f\noutcome win g h i\n");
+        for (idx, name) in ('A'..='I').enumerate() {
+            let beaten: Vec<String> = (1..=4).map(|v| format!("S{}", (idx + v) % 9)).collect();
+            let response = name.to_ascii_lowercase();
+            rules.push_str(&format!(
+                "shape S{idx} {name} {response} 1\nS{idx} beats {}\n",
+                beaten.join(" ")
+            ));
+        }
+
+        let rules = Rules::parse(&rules).unwrap();
+        let res = RockPaperScissors::with_rules("A a", rules).unwrap();
+        assert_eq!(
+            res.analyze().err().unwrap(),
+            "analyze reads rules with at most 7 shapes, not 9"
+        );
+    }
+
+    #[test]
+    fn trace() {
+        let res = RockPaperScissors::new_unwrap(EXAMPLE_INPUT);
+        let trace = res.trace();
+
+        assert_eq!(trace.len(), 6);
+        assert_eq!(
+            trace[1],
+            Step {
+                line: 1,
+                part: 2,
+                theirs: 0,
+                mine: 0,
+                outcome: Outcome::Draw,
+                shape_score: 1,
+                outcome_score: 3,
+                total: 4,
+            }
+        );
+        assert_eq!(trace[4].total, res.part1());
+        assert_eq!(trace[5].total, res.part2());
+
+        let csv = res.trace_report(true);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("line,part,opponent,mine,outcome,shape_score,outcome_score,total")
+        );
+        assert_eq!(lines.next(), Some("1,1,Rock,Paper,win,2,6,8"));
+        assert_eq!(lines.last(), Some("3,2,Scissors,Rock,win,1,6,12"));
+
+        let rules = RPS.replace("Rock", "Ro,ck").replace("Paper", "\"Paper\"");
+        let res = RockPaperScissors::with_rules(EXAMPLE_INPUT, Rules::parse(&rules).unwrap());
+        let csv = res.unwrap().trace_report(true);
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("1,1,\"Ro,ck\",\"\"\"Paper\"\"\",win,2,6,8")
+        );
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let shapes = "shape Rock A X 1\nshape Paper B Y 2\noutcome draw X Y\n";
+        let error = |relations: &str| Rules::parse(&format!("{shapes}{relations}")).unwrap_err();
+
+        assert!(Rules::parse(&format!("{shapes}Paper beats Rock")).is_ok());
+        assert_eq!(error("").message, "no winner between `Rock` and `Paper`");
+        assert_eq!(
+            error("Paper beats Rock\nRock beats Paper").position,
+            Some((5, 1))
+        );
+        assert_eq!(error("Paper beats Stone").message, "unknown shape `Stone`");
+        assert_eq!(
+            error("Paper beats Rock\noutcome win Q").message,
+            "`Q` is not the response of a shape"
+        );
+        assert_eq!(
+            error("Paper beats Rock\noutcome win Y").message,
+            "`Y` has more than one outcome"
+        );
+        assert_eq!(
+            error("shape Well C Z 3\noutcome win Z\nWell beats Rock Paper\nPaper beats Rock")
+                .message,
+            "no shape has the outcome win against `Well`"
+        );
+        assert_eq!(error("shape Rock C Z 3").position, Some((4, 1)));
+        assert_eq!(error("shape Spock E").position, Some((4, 14)));
+    }
+
+    #[test]
+    fn invalid_columns() {
+        let error = |input| RockPaperScissors::new(input).err().map(|e| e.position);
+
+        assert_eq!(error("A Y\nD X"), Some(Some((2, 1))));
+        assert_eq!(error("A Y\nB W"), Some(Some((2, 3))));
+        assert_eq!(error("A Y\nB XZ"), Some(Some((2, 4))));
+        assert_eq!(error("A Y\nBX"), Some(Some((2, 2))));
+
+        let e = RockPaperScissors::stream("A Y\nB X\nC  Z".as_bytes()).unwrap_err();
+        assert_eq!(e.position, Some((3, 3)));
+    }
 }
@@ -1,76 +1,356 @@
-pub struct RucksackReorganization {
-    rucksacks: Vec<String>,
+use crate::utils::{stream_lines, BitSet, ParseError};
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Every group of the puzzle has this many elves
+const GROUP_SIZE: usize = 3;
+
+/// A set of priorities, enough for an alphabet of 255 items
+type Items = BitSet<4>;
+
+/// The characters that are items, each with its priority
+pub struct Alphabet {
+    /// The items, the priority of an item is its index plus 1
+    items: Vec<char>,
+    /// The priorities of the ASCII items, 0 is not an item
+    ascii: [u8; 128],
+    other: HashMap<char, u8>,
 }
 
-fn char_to_idx(ch: char) -> usize {
-    match ch {
-        'a'..='z' => ch as usize - 'a' as usize + 1,
-        'A'..='Z' => ch as usize - 'A' as usize + 27,
-        _ => 0,
+impl Alphabet {
+    /// The items of `items` from the lowest priority, which starts at 1
+    pub fn new(items: &str) -> Result<Self, String> {
+        let mut res = Self {
+            items: Vec::new(),
+            ascii: [0; 128],
+            other: HashMap::new(),
+        };
+
+        for (item, priority) in items.chars().zip(1..) {
+            if priority >= Items::CAPACITY {
+                return Err(format!(
+                    "an alphabet has at most {} items",
+                    Items::CAPACITY - 1
+                ));
+            }
+
+            if item.is_whitespace() || res.priority(item).is_some() {
+                return Err(format!(
+                    "`{item}` cannot be an item twice, or be whitespace"
+                ));
+            }
+
+            match item.is_ascii() {
+                true => res.ascii[item as usize] = priority as u8,
+                false => _ = res.other.insert(item, priority as u8),
+            }
+
+            res.items.push(item);
+        }
+
+        Ok(res)
+    }
+
+    /// The items of the puzzle, `a` to `z` then `A` to `Z`
+    pub fn letters() -> Self {
+        let letters: String = ('a'..='z').chain('A'..='Z').collect();
+        Self::new(&letters).expect("the letters are distinct")
+    }
+
+    /// The built-in `letters` or `digits`, or any other text as its items from the lowest priority
+    fn load(name: &str) -> Result<Self, String> {
+        match name {
+            "letters" => Ok(Self::letters()),
+            "digits" => Self::new("0123456789"),
+            items => Self::new(items),
+        }
+    }
+
+    fn priority(&self, item: char) -> Option<usize> {
+        let priority = match item.is_ascii() {
+            true => self.ascii[item as usize],
+            false => *self.other.get(&item)?,
+        };
+
+        (priority > 0).then_some(priority as usize)
+    }
+
+    /// The priorities of the items, `s` was checked to only have items
+    fn items(&self, s: &str) -> Items {
+        s.chars().filter_map(|v| self.priority(v)).collect()
+    }
+
+    /// The first character of `s` that is not an item, as its column and itself
+    fn check(&self, s: &str) -> Option<(usize, char)> {
+        (1..).zip(s.chars()).find(|v| self.priority(v.1).is_none())
+    }
+
+    fn names(&self, items: Items) -> String {
+        let names: Vec<String> = items
+            .iter()
+            .map(|v| self.items[v - 1].to_string())
+            .collect();
+        names.join(", ")
     }
 }
 
-impl crate::AdventOfCode for RucksackReorganization {
-    const TITLE: &'static str = "Rucksack Reorganization";
-    const DAY: u8 = 3;
+fn priorities(items: Items) -> u64 {
+    items.iter().sum::<usize>() as u64
+}
+
+/// The two halves of a rucksack, with as many items in each one
+fn compartments(rucksack: &str) -> (&str, &str) {
+    let half = rucksack.chars().count() / 2;
+    let idx = rucksack
+        .char_indices()
+        .nth(half)
+        .map_or(rucksack.len(), |v| v.0);
 
-    fn new(input: &str) -> Option<Self> {
-        let rucksacks = crate::utils::lines_to_vec::<String>(input)?;
-        Some(Self { rucksacks })
+    rucksack.split_at(idx)
+}
+
+/// The priority of the items in both compartments of a rucksack
+fn misplaced(alphabet: &Alphabet, rucksack: &str) -> u64 {
+    let (first, second) = compartments(rucksack);
+    priorities(alphabet.items(first) & alphabet.items(second))
+}
+
+/// The priority of the only item of `common`, or which items it has otherwise
+fn single(alphabet: &Alphabet, common: Items) -> Result<u64, String> {
+    match common.len() {
+        1 => Ok(priorities(common)),
+        0 => Err("no common item".to_owned()),
+        n => Err(format!("{n} common items: {}", alphabet.names(common))),
     }
+}
 
-    fn part1(&self) -> u64 {
-        let mut priorities: u64 = 0;
+/// The rucksacks of a group for an error message
+fn quoted(group: &[impl AsRef<str>]) -> String {
+    let rucksacks: Vec<String> = group.iter().map(|v| format!("`{}`", v.as_ref())).collect();
+    rucksacks.join(", ")
+}
+
+/// The priority of the only item of a group that every elf has, `line` is the line of its first elf
+fn badge(alphabet: &Alphabet, group: &[impl AsRef<str>], line: usize) -> Result<u64, ParseError> {
+    let common = group.iter().fold(Items::full(), |common, v| {
+        common & alphabet.items(v.as_ref())
+    });
+
+    single(alphabet, common)
+        .map_err(|e| ParseError::new(format!("the group {} has {e}", quoted(group))).at(line, 1))
+}
+
+/// The error of a last group that is missing rucksacks, `line` is the line of its first elf
+fn incomplete(size: usize, group: &[impl AsRef<str>], line: usize) -> ParseError {
+    let count = match group.len() {
+        1 => "1 rucksack".to_owned(),
+        n => format!("{n} rucksacks"),
+    };
+    let message = format!(
+        "the last group {} has {count} instead of {size}",
+        quoted(group)
+    );
+    ParseError::new(message).at(line, 1)
+}
+
+fn validation_report(violations: &[ParseError]) -> String {
+    if violations.is_empty() {
+        return "Every rucksack and group follows the rules\n".to_owned();
+    }
+
+    let mut res = format!("{} violations:\n", violations.len());
+    for violation in violations {
+        res.push_str(&format!("{violation}\n"));
+    }
+
+    res
+}
+
+pub struct RucksackReorganization<'a> {
+    alphabet: Alphabet,
+    rucksacks: Vec<&'a str>,
+}
 
-        for rucksack in &self.rucksacks {
-            let mut list = [false; 53];
-            let mut list2 = [false; 53];
-            let middle_point = rucksack.len() / 2;
+impl<'a> RucksackReorganization<'a> {
+    /// Reads the rucksacks, which can only have items of `alphabet`
+    fn with_alphabet(input: &'a str, alphabet: Alphabet) -> Result<Self, ParseError> {
+        let mut rucksacks = Vec::new();
 
-            for ch in rucksack.get(..middle_point).unwrap().chars() {
-                let idx = char_to_idx(ch);
-                list[idx] = true;
+        for (rucksack, line) in input.lines().zip(1..) {
+            if let Some((column, item)) = alphabet.check(rucksack) {
+                let message = format!("`{item}` is not an item");
+                return Err(ParseError::new(message).at(line, column));
             }
 
-            for ch in rucksack.get(middle_point..).unwrap().chars() {
-                let idx = char_to_idx(ch);
-                list2[idx] = true;
+            rucksacks.push(rucksack);
+        }
+
+        Ok(Self {
+            alphabet,
+            rucksacks,
+        })
+    }
+
+    /// Every rucksack without an even number of items or a single misplaced item, and every
+    /// group of `size` rucksacks without a single badge, from the first line
+    fn validate(&self, size: usize) -> Vec<ParseError> {
+        let mut res = Vec::new();
+
+        for (rucksack, line) in self.rucksacks.iter().zip(1..) {
+            let len = rucksack.chars().count();
+            if len % 2 == 1 {
+                let message =
+                    format!("the rucksack `{rucksack}` has an odd number of items: {len}");
+                res.push(ParseError::new(message).at(line, 1));
+            }
+
+            let (first, second) = compartments(rucksack);
+            let common = self.alphabet.items(first) & self.alphabet.items(second);
+            if let Err(e) = single(&self.alphabet, common) {
+                let message = format!("the compartments `{first}` and `{second}` have {e}");
+                res.push(ParseError::new(message).at(line, 1));
+            }
+        }
+
+        let rest = self.rucksacks.len() % size;
+        let complete = &self.rucksacks[..self.rucksacks.len() - rest];
+
+        for (group, idx) in complete.chunks(size).zip((0..).step_by(size)) {
+            if let Err(e) = badge(&self.alphabet, group, idx + 1) {
+                res.push(e);
             }
+        }
+
+        if rest > 0 {
+            let group = &self.rucksacks[complete.len()..];
+            res.push(incomplete(size, group, complete.len() + 1));
+        }
 
-            priorities += (1..=52).filter(|&v| list[v] && list2[v]).sum::<usize>() as u64;
+        res.sort_by_key(|v| v.position);
+        res
+    }
+
+    /// Reads the rucksacks of the puzzle, and fails on the first rucksack or group that
+    /// breaks a rule of `validate` instead of only on the groups
+    pub fn strict(input: &'a str) -> Result<Self, ParseError> {
+        let res = Self::with_alphabet(input, Alphabet::letters())?;
+
+        match res.validate(GROUP_SIZE).into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(res),
         }
+    }
+
+    /// The sum of the badges of every group of `size` rucksacks
+    fn badges(&self, size: usize) -> Result<u64, ParseError> {
+        let rest = self.rucksacks.len() % size;
+        if rest > 0 {
+            let first = self.rucksacks.len() - rest;
+            return Err(incomplete(size, &self.rucksacks[first..], first + 1));
+        }
+
+        let mut res = 0;
+
+        for (group, idx) in self.rucksacks.chunks(size).zip((0..).step_by(size)) {
+            res += badge(&self.alphabet, group, idx + 1)?;
+        }
+
+        Ok(res)
+    }
+}
+
+impl<'a> crate::AdventOfCode<'a> for RucksackReorganization<'a> {
+    const TITLE: &'static str = "Rucksack Reorganization";
+    const DAY: u8 = 3;
+    const VERSION: u32 = 2;
+    const QUERIES: &'static [&'static str] = &["badges", "validate"];
 
-        priorities
+    fn new(input: &'a str) -> Result<Self, ParseError> {
+        let res = Self::with_alphabet(input, Alphabet::letters())?;
+
+        // part 2 has no answer without a single badge in every group
+        res.badges(GROUP_SIZE)?;
+
+        Ok(res)
+    }
+
+    fn part1(&self) -> u64 {
+        self.rucksacks
+            .iter()
+            .map(|v| misplaced(&self.alphabet, v))
+            .sum()
     }
 
     fn part2(&self) -> u64 {
-        let mut priorities: u64 = 0;
+        self.badges(GROUP_SIZE)
+            .expect("the groups were checked while parsing")
+    }
 
-        let mut iter = self.rucksacks.iter();
+    /// `badges <SIZE> [ALPHABET]` solves both parts with groups of SIZE elves, and
+    /// the `letters` or `digits` items, or the characters of ALPHABET from the lowest priority
+    ///
+    /// `validate [--strict]` lists every rucksack and group that breaks a rule of the puzzle,
+    /// or only the first one as a parsing error in strict mode
+    fn query(input: &'a str, name: &str, args: &[String]) -> Result<String, String> {
+        if name == "validate" {
+            return match args {
+                [] => Self::new_query(input, |v| Self::with_alphabet(v, Alphabet::letters()))
+                    .map(|v| validation_report(&v.validate(GROUP_SIZE))),
+                [strict] if strict == "--strict" => {
+                    Self::new_query(input, Self::strict).map(|_| validation_report(&[]))
+                }
+                _ => Err(format!("usage: {name} [--strict]")),
+            };
+        }
 
-        while let Some(rucksack) = iter.next() {
-            let mut list = [false; 53];
-            let mut list2 = [false; 53];
-            let mut list3 = [false; 53];
+        let (size, alphabet) = match args {
+            [size] => (size, Alphabet::letters()),
+            [size, alphabet] => (size, Alphabet::load(alphabet)?),
+            _ => return Err(format!("usage: {name} <SIZE> [letters|digits|ALPHABET]")),
+        };
 
-            for ch in rucksack.chars() {
-                list[char_to_idx(ch)] = true;
-            }
+        let size = match size.parse() {
+            Ok(0) | Err(_) => return Err(format!("invalid group size `{size}`")),
+            Ok(size) => size,
+        };
+
+        let f = Self::new_query(input, |v| Self::with_alphabet(v, alphabet))?;
+        let badges = f.badges(size).map_err(|e| format!("Invalid group: {e}"))?;
+
+        Ok(format!("Part 1: {}\nPart 2: {badges}\n", f.part1()))
+    }
+}
+
+impl<'a> crate::Streaming<'a> for RucksackReorganization<'a> {
+    fn stream(reader: impl BufRead) -> Result<[u64; 2], ParseError> {
+        let alphabet = Alphabet::letters();
+        let mut res = [0; 2];
+        let mut group = Vec::with_capacity(GROUP_SIZE);
+        let mut last = 0;
 
-            for ch in iter.next().unwrap().chars() {
-                list2[char_to_idx(ch)] = true;
+        for line in stream_lines(reader) {
+            let (number, rucksack) = line?;
+            last = number;
+
+            if let Some((column, item)) = alphabet.check(&rucksack) {
+                let message = format!("`{item}` is not an item");
+                return Err(ParseError::new(message).at(number, column));
             }
 
-            for ch in iter.next().unwrap().chars() {
-                list3[char_to_idx(ch)] = true;
+            res[0] += misplaced(&alphabet, &rucksack);
+            group.push(rucksack);
+
+            if group.len() == GROUP_SIZE {
+                res[1] += badge(&alphabet, &group, number + 1 - GROUP_SIZE)?;
+                group.clear();
             }
+        }
 
-            priorities += (1..=52)
-                .filter(|&v| list[v] && list2[v] && list3[v])
-                .sum::<usize>() as u64;
+        if !group.is_empty() {
+            return Err(incomplete(GROUP_SIZE, &group, last + 1 - group.len()));
         }
 
-        priorities
+        Ok(res)
     }
 }
 
@@ -97,4 +377,104 @@ CrZsJsPPZsGzwwsLwLmpwMDw"#;
         let res = RucksackReorganization::new_unwrap(EXAMPLE_INPUT);
         assert_eq!(res.part2(), 70);
     }
+
+    #[test]
+    fn stream() {
+        let res = RucksackReorganization::stream(EXAMPLE_INPUT.as_bytes());
+        assert_eq!(res, Ok([157, 70]));
+    }
+
+    #[test]
+    fn larger_groups() {
+        let res = RucksackReorganization::new_unwrap(EXAMPLE_INPUT);
+        let error = |size| res.badges(size).unwrap_err().to_string();
+        let rucksacks: Vec<&str> = EXAMPLE_INPUT.lines().collect();
+
+        assert_eq!(
+            error(2),
+            format!(
+                "line 1, column 1: the group {} has 5 common items: f, r, s, F, M",
+                quoted(&rucksacks[..2])
+            )
+        );
+        assert_eq!(
+            error(6),
+            format!(
+                "line 1, column 1: the group {} has no common item",
+                quoted(&rucksacks)
+            )
+        );
+        assert_eq!(
+            error(4),
+            format!(
+                "line 5, column 1: the last group {} has 2 rucksacks instead of 4",
+                quoted(&rucksacks[4..])
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_groups() {
+        let input = format!("{EXAMPLE_INPUT}\nab");
+        let e = RucksackReorganization::new(&input).err().unwrap();
+        assert_eq!(e.position, Some((7, 1)));
+
+        let e = RucksackReorganization::stream(input.as_bytes()).unwrap_err();
+        assert_eq!(e.position, Some((7, 1)));
+
+        let input = "ab\nac\nad\nbc\nbd\nce";
+        let e = RucksackReorganization::new(input).err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "line 4, column 1: the group `bc`, `bd`, `ce` has no common item"
+        );
+        assert_eq!(RucksackReorganization::stream(input.as_bytes()), Err(e));
+
+        let e = RucksackReorganization::new("vJrw\nJg3W\nJJ").err().unwrap();
+        assert_eq!(e.to_string(), "line 2, column 3: `3` is not an item");
+    }
+
+    #[test]
+    fn validation() {
+        let res = RucksackReorganization::new_unwrap(EXAMPLE_INPUT);
+        assert!(res.validate(3).is_empty());
+        assert!(RucksackReorganization::strict(EXAMPLE_INPUT).is_ok());
+
+        let input = "abcab\nabab\nxyaz\nzz";
+        let res = RucksackReorganization::with_alphabet(input, Alphabet::letters()).unwrap();
+        let violations =
+            |size| -> Vec<String> { res.validate(size).iter().map(|v| v.to_string()).collect() };
+        assert_eq!(
+            violations(3),
+            [
+                "line 1, column 1: the rucksack `abcab` has an odd number of items: 5",
+                "line 1, column 1: the compartments `ab` and `cab` have 2 common items: a, b",
+                "line 2, column 1: the compartments `ab` and `ab` have 2 common items: a, b",
+                "line 3, column 1: the compartments `xy` and `az` have no common item",
+                "line 4, column 1: the last group `zz` has 1 rucksack instead of 3",
+            ]
+        );
+        assert!(violations(2).contains(
+            &"line 1, column 1: the group `abcab`, `abab` has 2 common items: a, b".to_owned()
+        ));
+
+        let e = RucksackReorganization::strict(input).err().unwrap();
+        assert_eq!(e.to_string(), violations(3)[0]);
+    }
+
+    #[test]
+    fn alphabets() {
+        let input = "1231\n4353\n3993";
+        let res = RucksackReorganization::with_alphabet(input, Alphabet::load("digits").unwrap())
+            .unwrap();
+        assert_eq!((res.part1(), res.badges(3)), (2 + 4 + (4 + 10), Ok(4)));
+
+        let input = "αβγα\nδβεβ";
+        let alphabet = Alphabet::new("αβγδε").unwrap();
+        let res = RucksackReorganization::with_alphabet(input, alphabet).unwrap();
+        assert_eq!((res.part1(), res.badges(2)), (1 + 2, Ok(2)));
+
+        assert!(Alphabet::new("abca").is_err());
+        assert!(Alphabet::new(&"ab".repeat(200)).is_err());
+    }
 }
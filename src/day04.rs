@@ -1,48 +1,105 @@
-struct Range {
-    from: u64,
-    to: u64,
+use crate::utils::parse::{self, lines, literal, unsigned, ParseResult, Parser};
+use crate::utils::{stream_lines, Interval, IntervalSet, ParseError, Section};
+use std::io::BufRead;
+
+fn range(input: &str) -> ParseResult<'_, Interval> {
+    unsigned
+        .skip(literal("-"))
+        .and(unsigned)
+        .try_map(
+            "a range that does not end before it starts",
+            |(from, to)| Interval::new(from, to),
+        )
+        .parse(input)
 }
 
-impl Range {
-    fn from_str(s: &str) -> Option<Self> {
-        let mut iter = s.split('-');
-        let from = iter.next()?.parse::<u64>().ok()?;
-        let to = iter.next()?.parse::<u64>().ok()?;
-        Some(Self { from, to })
-    }
+fn pair(input: &str) -> ParseResult<'_, (Interval, Interval)> {
+    range.skip(literal(",")).and(range).parse(input)
+}
 
-    fn contains(&self, other: &Self) -> bool {
-        self.from <= other.from && self.to >= other.to
+fn sections_report(what: &str, sections: &IntervalSet) -> String {
+    if sections.is_empty() {
+        return format!("No section is {what}\n");
     }
 
-    fn overlap(&self, other: &Self) -> bool {
-        if self.from < other.from {
-            self.to >= other.from
-        } else {
-            other.to >= self.from
-        }
-    }
+    let intervals: Vec<String> = sections.intervals().iter().map(|v| v.to_string()).collect();
+    let count = match sections.coverage() {
+        1 => "1 section is".to_owned(),
+        n => format!("{n} sections are"),
+    };
+
+    format!("{count} {what}: {}\n", intervals.join(", "))
 }
 
 pub struct CampCleanup {
-    pairs: Vec<(Range, Range)>,
+    pairs: Vec<(Interval, Interval)>,
 }
 
-impl crate::AdventOfCode for CampCleanup {
-    const TITLE: &'static str = "Camp Cleanup";
-    const DAY: u8 = 4;
+impl CampCleanup {
+    fn ranges(&self) -> impl Iterator<Item = Interval> + '_ {
+        self.pairs.iter().flat_map(|&(a, b)| [a, b])
+    }
+
+    /// The sections cleaned by at least one elf, and by at least two of them, in
+    /// a single sweep over the sorted ends of the ranges
+    fn coverage(&self) -> (IntervalSet, IntervalSet) {
+        // `false` for the start, so a range starting at the section another one
+        // ends at is counted with it, as both ranges include that section
+        let mut ends: Vec<(u64, bool)> = self
+            .ranges()
+            .flat_map(|v| [(v.from, false), (v.to, true)])
+            .collect();
+        ends.sort_unstable();
+
+        // the parts cleaned by at least 1 and by at least 2 elves
+        let mut covered = [Vec::new(), Vec::new()];
+        let mut starts = [0; 2];
+        let mut depth = 0;
 
-    fn new(input: &str) -> Option<Self> {
-        let mut pairs = Vec::new();
+        for (section, is_end) in ends {
+            if is_end {
+                if depth <= 2 {
+                    covered[depth - 1].push(Interval {
+                        from: starts[depth - 1],
+                        to: section,
+                    });
+                }
+                depth -= 1;
+            } else {
+                depth += 1;
+                if depth <= 2 {
+                    starts[depth - 1] = section;
+                }
+            }
+        }
+
+        let [once, twice] = covered.map(IntervalSet::from_iter);
+        (once, twice)
+    }
 
-        for line in input.lines() {
-            let mut iter = line.split(",");
-            let a = Range::from_str(iter.next()?)?;
-            let b = Range::from_str(iter.next()?)?;
-            pairs.push((a, b));
+    /// The sections from the first to the last one assigned that nobody cleans
+    fn uncleaned(&self) -> IntervalSet {
+        let (cleaned, _) = self.coverage();
+
+        match (cleaned.intervals().first(), cleaned.intervals().last()) {
+            (Some(first), Some(last)) => cleaned.gaps(Interval {
+                from: first.from,
+                to: last.to,
+            }),
+            _ => IntervalSet::new(),
         }
+    }
+}
 
-        Some(Self { pairs })
+impl crate::AdventOfCode<'_> for CampCleanup {
+    const TITLE: &'static str = "Camp Cleanup";
+    const DAY: u8 = 4;
+    const VERSION: u32 = 2;
+    const QUERIES: &'static [&'static str] = &["uncleaned", "twice"];
+
+    fn new(input: &str) -> Result<Self, ParseError> {
+        let pairs = parse::all(input, lines(pair))?;
+        Ok(Self { pairs })
     }
 
     fn part1(&self) -> u64 {
@@ -53,7 +110,39 @@ impl crate::AdventOfCode for CampCleanup {
     }
 
     fn part2(&self) -> u64 {
-        self.pairs.iter().filter(|(a, b)| a.overlap(b)).count() as u64
+        self.pairs.iter().filter(|(a, b)| a.overlaps(b)).count() as u64
+    }
+
+    /// `uncleaned` lists the sections between the first and the last assigned one that
+    /// no elf cleans, `twice` the sections that at least two elves clean
+    fn query(input: &str, name: &str, args: &[String]) -> Result<String, String> {
+        let f = Self::new_query(input, Self::new)?;
+
+        match (name, args) {
+            ("uncleaned", []) => Ok(sections_report("cleaned by nobody", &f.uncleaned())),
+            ("twice", []) => Ok(sections_report("cleaned twice", &f.coverage().1)),
+            _ => Err(format!("usage: {name}")),
+        }
+    }
+}
+
+impl crate::Streaming<'_> for CampCleanup {
+    fn stream(reader: impl BufRead) -> Result<[u64; 2], ParseError> {
+        let mut res = [0; 2];
+
+        for line in stream_lines(reader) {
+            let (number, line) = line?;
+            let section = Section {
+                text: &line,
+                line: number,
+            };
+            let (a, b) = section.parse(pair)?;
+
+            res[0] += (a.contains(&b) || b.contains(&a)) as u64;
+            res[1] += a.overlaps(&b) as u64;
+        }
+
+        Ok(res)
     }
 }
 
@@ -69,6 +158,12 @@ mod tests {
 6-6,4-6
 2-6,4-8"#;
 
+    #[test]
+    fn stream() {
+        let res = CampCleanup::stream(EXAMPLE_INPUT.as_bytes());
+        assert_eq!(res, Ok([2, 4]));
+    }
+
     #[test]
     fn part1() {
         let res = CampCleanup::new_unwrap(EXAMPLE_INPUT);
@@ -80,4 +175,40 @@ mod tests {
         let res = CampCleanup::new_unwrap(EXAMPLE_INPUT);
         assert_eq!(res.part2(), 4);
     }
+
+    #[test]
+    fn sections() {
+        let res = CampCleanup::new_unwrap(EXAMPLE_INPUT);
+        let intervals = |set: IntervalSet| set.intervals().to_vec();
+
+        assert_eq!(intervals(res.uncleaned()), []);
+        assert_eq!(intervals(res.coverage().1), [Interval { from: 2, to: 8 }]);
+
+        let res = CampCleanup::new_unwrap("2-3,9-9\n5-6,8-9");
+        assert_eq!(
+            sections_report("cleaned by nobody", &res.uncleaned()),
+            "2 sections are cleaned by nobody: 4-4, 7-7\n"
+        );
+        assert_eq!(
+            sections_report("cleaned twice", &res.coverage().1),
+            "1 section is cleaned twice: 9-9\n"
+        );
+
+        let res = CampCleanup::new_unwrap("0-18446744073709551615,0-18446744073709551615");
+        assert_eq!(
+            sections_report("cleaned twice", &res.coverage().1),
+            "18446744073709551616 sections are cleaned twice: 0-18446744073709551615\n"
+        );
+
+        let res = CampCleanup::new_unwrap("1-3,3-5\n4-4,7-9\n4-4,10-10");
+        let (once, twice) = res.coverage();
+        for section in 0..12 {
+            let count = res.ranges().filter(|v| v.contains_value(section)).count();
+            assert_eq!(once.contains(section), count >= 1);
+            assert_eq!(twice.contains(section), count >= 2);
+        }
+
+        let e = CampCleanup::new("2-4,6-8\n3-2,4-5").err().unwrap();
+        assert_eq!(e.position, Some((2, 1)));
+    }
 }
@@ -1,3 +1,9 @@
+use crate::utils::parse::{
+    any_char, line_ending, lines, literal, spaces, unsigned, ParseResult, Parser,
+};
+use crate::utils::{ParseError, Sections};
+use crate::Normalize;
+
 struct Instruction {
     take: usize,
     from: usize,
@@ -5,25 +11,34 @@ struct Instruction {
 }
 
 impl Instruction {
-    fn from_str(s: &str) -> Option<Self> {
-        let mut iter = s.split_whitespace();
-        let mut res = Self {
-            take: 0,
-            from: 0,
-            to: 0,
-        };
-
-        iter.next().filter(|&v| v == "move")?;
-        res.take = iter.next()?.parse().ok()?;
-
-        iter.next().filter(|&v| v == "from")?;
-        res.from = iter.next()?.parse().ok()?;
+    fn parse(input: &str) -> ParseResult<'_, Self> {
+        literal("move ")
+            .then(unsigned)
+            .skip(literal(" from "))
+            .and(unsigned)
+            .skip(literal(" to "))
+            .and(unsigned)
+            .map(|((take, from), to)| Self { take, from, to })
+            .parse(input)
+    }
+}
 
-        iter.next().filter(|&v| v == "to")?;
-        res.to = iter.next()?.parse().ok()?;
+/// A crate like `[A]`, or 3 spaces where there is none
+fn crate_slot(input: &str) -> ParseResult<'_, Option<char>> {
+    literal("[")
+        .then(any_char)
+        .skip(literal("]"))
+        .map(Some)
+        .or(literal("   ").map(|_| None))
+        .parse(input)
+}
 
-        Some(res)
-    }
+/// The numbers below the stacks, like ` 1   2   3 `
+fn labels(input: &str) -> ParseResult<'_, Vec<usize>> {
+    spaces
+        .then(unsigned.sep_by(spaces))
+        .skip(spaces)
+        .parse(input)
 }
 
 #[derive(Default)]
@@ -32,48 +47,43 @@ pub struct SupplyStacks {
     instructions: Vec<Instruction>,
 }
 
-impl crate::AdventOfCode<String> for SupplyStacks {
+impl crate::AdventOfCode<'_, String> for SupplyStacks {
     const TITLE: &'static str = "Supply Stacks";
     const DAY: u8 = 5;
+    // the empty spots of the drawing are spaces, even at the end of a line
+    const NORMALIZE: Normalize = Normalize::LineBreaks;
 
-    fn new(input: &str) -> Option<Self> {
-        let mut iter = input.lines();
-        let mut res = Self::default();
-
-        while let Some(line) = iter.next() {
-            if line.starts_with(" 1 ") {
-                iter.next();
-                break;
-            }
+    fn new(input: &str) -> Result<Self, ParseError> {
+        // no stacks and no instructions, like the empty inputs of the days read line by line
+        if input.trim().is_empty() {
+            return Ok(Self::default());
+        }
 
-            let mut chars = line.chars();
-            let mut current_idx = 0;
+        let drawing = crate_slot.sep_by(literal(" ")).sep_by(line_ending);
+        let mut sections = Sections::new(input);
+        let (rows, labels) = sections.parse(drawing.skip(line_ending).and(labels))?;
+        let instructions = sections.parse(lines(Instruction::parse))?;
+        sections.finish()?;
 
-            while let Some(ch) = chars.next() {
-                if current_idx == res.stacks.len() {
-                    res.stacks.push(Default::default());
-                }
+        let mut stacks = vec![Vec::new(); labels.len()];
 
-                if ch == ' ' {
-                    chars.next().filter(|&v| v == ' ')?;
-                    chars.next().filter(|&v| v == ' ')?;
-                } else if ch == '[' {
-                    res.stacks[current_idx].insert(0, chars.next()?);
-                    chars.next().filter(|&v| v == ']')?;
-                } else {
-                    return None;
-                }
+        for row in rows.iter().rev() {
+            for (idx, ch) in row.iter().enumerate() {
+                let Some(ch) = ch else {
+                    continue;
+                };
 
-                chars.next();
-                current_idx += 1;
+                stacks
+                    .get_mut(idx)
+                    .ok_or_else(|| ParseError::new("there are more crates than stacks"))?
+                    .push(*ch);
             }
         }
 
-        for line in iter {
-            res.instructions.push(Instruction::from_str(line)?);
-        }
-
-        Some(res)
+        Ok(Self {
+            stacks,
+            instructions,
+        })
     }
 
     fn part1(&self) -> String {
@@ -135,4 +145,10 @@ move 1 from 1 to 2"#;
         let res = SupplyStacks::new_unwrap(EXAMPLE_INPUT);
         assert_eq!(res.part2(), "MCD");
     }
+
+    #[test]
+    fn empty_input() {
+        let res = SupplyStacks::new_unwrap("");
+        assert_eq!((res.part1(), res.part2()), (String::new(), String::new()));
+    }
 }
@@ -1,10 +1,19 @@
-pub struct TuningTrouble {
-    buffer: Vec<char>,
+use crate::utils::ParseError;
+use std::io::BufRead;
+
+pub struct TuningTrouble<'a> {
+    buffer: &'a [u8],
 }
 
-fn tuning_trouble<const N: usize>(buffer: &[char]) -> usize {
+/// The position after the first N different bytes, or the length of the
+/// datastream when there are none
+fn tuning_trouble<const N: usize>(buffer: &[u8]) -> usize {
+    let Some(start) = buffer.get(..N) else {
+        return buffer.len();
+    };
+
     let mut count = N;
-    let mut chars: [char; N] = buffer[..N].try_into().unwrap();
+    let mut chars: [u8; N] = start.try_into().unwrap();
 
     while !is_unique_arr(&chars) {
         let Some(ch) = buffer.get(count) else {
@@ -18,7 +27,7 @@ fn tuning_trouble<const N: usize>(buffer: &[char]) -> usize {
     count
 }
 
-fn is_unique_arr(arr: &[char]) -> bool {
+fn is_unique_arr(arr: &[u8]) -> bool {
     for i in 0..(arr.len() - 1) {
         for j in (i + 1)..arr.len() {
             if arr[i] == arr[j] {
@@ -30,22 +39,69 @@ fn is_unique_arr(arr: &[char]) -> bool {
     true
 }
 
-impl crate::AdventOfCode<usize> for TuningTrouble {
+impl<'a> crate::AdventOfCode<'a, usize> for TuningTrouble<'a> {
     const TITLE: &'static str = "Tuning Trouble";
     const DAY: u8 = 6;
+    const VERSION: u32 = 2;
 
-    fn new(input: &str) -> Option<Self> {
-        Some(Self {
-            buffer: input.chars().collect(),
+    fn new(input: &'a str) -> Result<Self, ParseError> {
+        // the datastream ends with its line, like in `stream`
+        let line = input.split(['\n', '\r']).next().unwrap_or_default();
+
+        Ok(Self {
+            buffer: line.as_bytes(),
         })
     }
 
     fn part1(&self) -> usize {
-        tuning_trouble::<4>(&self.buffer)
+        tuning_trouble::<4>(self.buffer)
     }
 
     fn part2(&self) -> usize {
-        tuning_trouble::<14>(&self.buffer)
+        tuning_trouble::<14>(self.buffer)
+    }
+}
+
+impl<'a> crate::Streaming<'a, usize> for TuningTrouble<'a> {
+    /// Reads the datastream byte by byte, so it does not have to end in a line
+    fn stream(mut reader: impl BufRead) -> Result<[usize; 2], ParseError> {
+        const SIZES: [usize; 2] = [4, 14];
+        const BOM: &[u8] = "\u{feff}".as_bytes();
+
+        let error = |e| ParseError::new(format!("cannot read the input: {e}"));
+
+        if reader.fill_buf().map_err(error)?.starts_with(BOM) {
+            reader.consume(BOM.len());
+        }
+
+        // the position after the last time each byte was seen
+        let mut last_seen = [0; 256];
+        // the start of the longest run of different bytes before the current one
+        let mut starts = [0; 2];
+        let mut markers = [None; 2];
+        let mut count = 0;
+
+        for byte in reader.bytes() {
+            let byte = byte.map_err(error)?;
+
+            if matches!(byte, b'\n' | b'\r') || markers.iter().all(Option::is_some) {
+                break;
+            }
+
+            count += 1;
+
+            for idx in 0..2 {
+                starts[idx] = starts[idx].max(last_seen[byte as usize]);
+
+                if markers[idx].is_none() && count - starts[idx] >= SIZES[idx] {
+                    markers[idx] = Some(count);
+                }
+            }
+
+            last_seen[byte as usize] = count;
+        }
+
+        Ok(markers.map(|v| v.unwrap_or(count)))
     }
 }
 
@@ -60,6 +116,25 @@ mod tests {
     const EXAMPLE_INPUT_4: &str = r"nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
     const EXAMPLE_INPUT_5: &str = r"zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
 
+    #[test]
+    fn stream() {
+        let res = TuningTrouble::stream(EXAMPLE_INPUT.as_bytes());
+        assert_eq!(res, Ok([7, 19]));
+
+        // without a marker the answer is the length of the datastream, not of the line
+        let res = TuningTrouble::stream("abcabc\r\n".as_bytes());
+        assert_eq!(res, Ok([6, 6]));
+    }
+
+    #[test]
+    fn short_datastreams() {
+        for (input, expected) in [("", [0, 0]), ("ab\n", [2, 2]), ("abcdab\r\nefgh", [4, 6])] {
+            let res = TuningTrouble::new_unwrap(input);
+            assert_eq!([res.part1(), res.part2()], expected);
+            assert_eq!(TuningTrouble::stream(input.as_bytes()), Ok(expected));
+        }
+    }
+
     #[test]
     fn part1() {
         let res = TuningTrouble::new_unwrap(EXAMPLE_INPUT);
@@ -1,56 +1,65 @@
+use crate::utils::parse::{self, line_ending, lines, literal, unsigned, word, ParseResult, Parser};
+use crate::utils::ParseError;
 use std::collections::HashMap;
 
 #[derive(Debug)]
-struct DirEntry {
+struct DirEntry<'a> {
     size: Option<usize>,
-    name: String,
+    name: &'a str,
 }
 
-impl DirEntry {
-    fn from_str(s: &str) -> Option<Self> {
-        let mut iter = s.split(" ");
-        let size = match iter.next() {
-            Some("dir") => None,
-            Some(v) => Some(v.parse::<usize>().ok()?),
-            None => return None,
-        };
-
-        Some(Self {
-            size,
-            name: iter.collect::<String>(),
-        })
+impl<'a> DirEntry<'a> {
+    fn parse(input: &'a str) -> ParseResult<'a, Self> {
+        let dir = literal("dir ").then(word).map(|name| (None, name));
+        let file = unsigned.map(Some).skip(literal(" ")).and(word);
+
+        dir.or(file)
+            .map(|(size, name)| Self { size, name })
+            .parse(input)
     }
 }
 
-enum Command {
-    Cd { arg: String },
-    Ls { output: Vec<DirEntry> },
+enum Command<'a> {
+    Cd { arg: &'a str },
+    Ls { output: Vec<DirEntry<'a>> },
+}
+
+impl<'a> Command<'a> {
+    fn parse(input: &'a str) -> ParseResult<'a, Self> {
+        let cd = literal("$ cd ").then(word).map(|arg| Self::Cd { arg });
+        let ls = literal("$ ls")
+            .then(line_ending.then(DirEntry::parse).many())
+            .map(|output| Self::Ls { output });
+
+        cd.or(ls).parse(input)
+    }
 }
 
 #[derive(Default)]
-pub struct NoSpaceLeftOnDevice {
-    cmds: Vec<Command>,
+pub struct NoSpaceLeftOnDevice<'a> {
+    cmds: Vec<Command<'a>>,
 }
 
-impl NoSpaceLeftOnDevice {
-    fn file_list(&self) -> HashMap<Vec<String>, usize> {
+impl<'a> NoSpaceLeftOnDevice<'a> {
+    fn file_list(&self) -> HashMap<Vec<&'a str>, usize> {
+        let _s = span!("file_list");
         let mut res = HashMap::new();
         let mut current_dir = Vec::new();
         for cmd in &self.cmds {
             match cmd {
-                Command::Cd { arg } => match arg.as_str() {
+                Command::Cd { arg } => match *arg {
                     "/" => current_dir.clear(),
                     ".." => {
                         current_dir.pop();
                     }
-                    v => current_dir.push(v.to_owned()),
+                    v => current_dir.push(v),
                 },
 
                 Command::Ls { output } => {
                     for entry in output {
                         if let Some(size) = entry.size {
                             let mut path = current_dir.to_owned();
-                            path.push(entry.name.to_owned());
+                            path.push(entry.name);
                             res.insert(path, size);
                         }
                     }
@@ -61,7 +70,8 @@ impl NoSpaceLeftOnDevice {
         res
     }
 
-    fn dir_list(&self) -> HashMap<Vec<String>, usize> {
+    fn dir_list(&self) -> HashMap<Vec<&'a str>, usize> {
+        let _s = span!("dir_list");
         let mut list = HashMap::new();
 
         for (mut path, size) in self.file_list() {
@@ -82,43 +92,13 @@ impl NoSpaceLeftOnDevice {
     }
 }
 
-impl crate::AdventOfCode<usize> for NoSpaceLeftOnDevice {
+impl<'a> crate::AdventOfCode<'a, usize> for NoSpaceLeftOnDevice<'a> {
     const TITLE: &'static str = "No Space Left On Device";
     const DAY: u8 = 7;
 
-    fn new(input: &str) -> Option<Self> {
-        let mut iter = input.lines().peekable();
-        let mut res = Self::default();
-
-        while let Some(line) = iter.next() {
-            let mut args = line.split_whitespace();
-            args.next().filter(|&v| v == "$")?;
-
-            let cmd = match args.next()? {
-                "cd" => Command::Cd {
-                    arg: args.next()?.to_owned(),
-                },
-                "ls" => {
-                    let mut output = Vec::new();
-
-                    while let Some(line) = iter.peek() {
-                        if line.starts_with("$ ") {
-                            break;
-                        }
-
-                        let entry = DirEntry::from_str(iter.next()?)?;
-                        output.push(entry);
-                    }
-
-                    Command::Ls { output }
-                }
-                _ => return None,
-            };
-
-            res.cmds.push(cmd);
-        }
-
-        Some(res)
+    fn new(input: &'a str) -> Result<Self, ParseError> {
+        let cmds = parse::all(input, lines(Command::parse))?;
+        Ok(Self { cmds })
     }
 
     fn part1(&self) -> usize {
@@ -130,7 +110,9 @@ impl crate::AdventOfCode<usize> for NoSpaceLeftOnDevice {
         const NEEDED_SPACE: usize = 30000000;
 
         let dir_list = self.dir_list();
-        let free_space = TOTAL_SPACE - dir_list.get(&Vec::default()).unwrap();
+        // there is no root without any file
+        let used = dir_list.get(&Vec::default()).copied().unwrap_or_default();
+        let free_space = TOTAL_SPACE - used;
 
         dir_list
             .values()
@@ -181,4 +163,10 @@ $ ls
         let res = NoSpaceLeftOnDevice::new_unwrap(EXAMPLE_INPUT);
         assert_eq!(res.part2(), 24933642);
     }
+
+    #[test]
+    fn empty_input() {
+        let res = NoSpaceLeftOnDevice::new_unwrap("");
+        assert_eq!((res.part1(), res.part2()), (0, 0));
+    }
 }
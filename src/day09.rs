@@ -1,116 +1,137 @@
+use crate::utils::parse::{self, lines, literal, unsigned, word, ParseResult, Parser};
+use crate::utils::{stream_lines, Direction, ParseError, Point, Section};
 use std::collections::HashSet;
+use std::io::BufRead;
 
-type Coor = (isize, isize);
+fn tail_move(tail: &mut Point, head: Point) {
+    if tail.chebyshev(head) > 1 {
+        *tail += (head - *tail).signum();
+    }
+}
 
-#[derive(Clone, Copy)]
-enum Direction {
-    Right,
-    Up,
-    Left,
-    Down,
+/// A head with `N` knots behind it
+struct Rope<const N: usize> {
+    head: Point,
+    tails: [Point; N],
 }
 
-impl Direction {
-    fn modification(&self) -> (isize, isize) {
-        match self {
-            Self::Up => (-1, 0),
-            Self::Down => (1, 0),
-            Self::Left => (0, -1),
-            Self::Right => (0, 1),
+impl<const N: usize> Rope<N> {
+    fn new() -> Self {
+        Self {
+            head: Point::ORIGIN,
+            tails: [Point::ORIGIN; N],
         }
     }
-}
 
-fn tail_move(tail: &mut Coor, head: Coor) {
-    let x_distance = head.0 - tail.0;
-    let y_distance = head.1 - tail.1;
-    
-    if x_distance > 1 {
-        tail.0 += 1;
-        tail.1 += y_distance.clamp(-1, 1);
-    } else if x_distance < -1 {
-        tail.0 -= 1;
-        tail.1 += y_distance.clamp(-1, 1);
-    } else if y_distance > 1 {
-        tail.1 += 1;
-        tail.0 += x_distance.clamp(-1, 1);
-    } else if y_distance < -1 {
-        tail.1 -= 1;
-        tail.0 += x_distance.clamp(-1, 1);
+    fn step(&mut self, direction: Direction) {
+        self.head += direction.vector();
+
+        let mut current_head = self.head;
+
+        for tail in self.tails.iter_mut() {
+            tail_move(tail, current_head);
+            current_head = *tail;
+        }
     }
 }
 
+fn motion(input: &str) -> ParseResult<'_, (Direction, isize)> {
+    let direction = word.try_map("U, D, L or R", |v| match v {
+        "U" => Some(Direction::North),
+        "D" => Some(Direction::South),
+        "L" => Some(Direction::West),
+        "R" => Some(Direction::East),
+        _ => None,
+    });
+    direction.skip(literal(" ")).and(unsigned).parse(input)
+}
+
 #[derive(Default)]
 pub struct RopeBridge {
-    motions: Vec<(Direction, isize)>
+    motions: Vec<(Direction, isize)>,
 }
 
-impl crate::AdventOfCode<usize> for RopeBridge {
+impl RopeBridge {
+    /// Positions visited by the last knot of a rope with `N` knots behind the head
+    fn visited<const N: usize>(&self) -> HashSet<Point> {
+        let mut visited = HashSet::new();
+        let mut rope = Rope::<N>::new();
+
+        for (direction, val) in self.motions.iter().copied() {
+            for _ in 0..val {
+                rope.step(direction);
+                visited.insert(rope.tails[N - 1]);
+            }
+        }
+
+        visited
+    }
+}
+
+impl crate::AdventOfCode<'_, usize> for RopeBridge {
     const TITLE: &'static str = "Rope Bridge";
     const DAY: u8 = 9;
 
-    fn new(input: &str) -> Option<Self> {
-        let mut res = Self::default();
-
-        for line in input.lines() {
-            let mut iter = line.split_whitespace();
-            let direction = match iter.next()? {
-                "R" => Direction::Right,
-                "L" => Direction::Left,
-                "U" => Direction::Up,
-                "D" => Direction::Down,
-                _ => return None,
-            };
+    fn new(input: &str) -> Result<Self, ParseError> {
+        let motions = parse::all(input, lines(motion))?;
+        Ok(Self { motions })
+    }
 
-            let val = iter.next()?.parse::<isize>().ok()?;
-            iter.next().is_none().then_some(())?;
-            res.motions.push((direction, val));
-        }
+    fn part1(&self) -> usize {
+        self.visited::<1>().len()
+    }
 
-        Some(res)
+    fn part2(&self) -> usize {
+        self.visited::<9>().len()
     }
 
-    fn part1(&self) -> usize {
-        let mut visited = HashSet::new();
-        let mut head = (0, 0);
-        let mut tail = (0, 0);
-        
-        for (direction, val) in self.motions.iter().copied() {
-            for _ in 0..val {
-                let (x_mod, y_mod) = direction.modification();
-                head.0 += x_mod;
-                head.1 += y_mod;
-                tail_move(&mut tail, head);
-                visited.insert(tail);
+    fn visualize(&self) -> Option<String> {
+        let visited = self.visited::<1>();
+        let min_x = visited.iter().map(|v| v.x).min()?;
+        let max_x = visited.iter().map(|v| v.x).max()?;
+        let min_y = visited.iter().map(|v| v.y).min()?;
+        let max_y = visited.iter().map(|v| v.y).max()?;
+        let mut res = String::new();
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let ch = match Point::new(x, y) {
+                    Point::ORIGIN => 's',
+                    point if visited.contains(&point) => '#',
+                    _ => '.',
+                };
+
+                res.push(ch);
             }
+
+            res.push('\n');
         }
 
-        visited.len()
+        Some(res)
     }
+}
 
-    fn part2(&self) -> usize {
-        let mut visited = HashSet::new();
-        let mut head = (0, 0);
-        let mut tails = [(0, 0); 9];
-        
-        for (direction, val) in self.motions.iter().copied() {
-            for _ in 0..val {
-                let (x_mod, y_mod) = direction.modification();
-                head.0 += x_mod;
-                head.1 += y_mod;
-
-                let mut current_head = head;
+impl crate::Streaming<'_, usize> for RopeBridge {
+    fn stream(reader: impl BufRead) -> Result<[usize; 2], ParseError> {
+        let mut visited = [HashSet::new(), HashSet::new()];
+        let mut rope = Rope::<9>::new();
 
-                for tail in tails.iter_mut() {
-                    tail_move(tail, current_head);
-                    current_head = *tail;
-                }
+        for line in stream_lines(reader) {
+            let (number, line) = line?;
+            let section = Section {
+                text: &line,
+                line: number,
+            };
+            let (direction, val) = section.parse(motion)?;
 
-                visited.insert(current_head);
+            for _ in 0..val {
+                rope.step(direction);
+                visited[0].insert(rope.tails[0]);
+                visited[1].insert(rope.tails[8]);
             }
         }
 
-        visited.len()
+        Ok(visited.map(|v| v.len()))
     }
 }
 
@@ -134,6 +155,21 @@ R 2"#;
         assert_eq!(res.part1(), 13);
     }
 
+    #[test]
+    fn stream() {
+        let res = RopeBridge::stream(EXAMPLE_INPUT.as_bytes());
+        assert_eq!(res, Ok([13, 1]));
+    }
+
+    #[test]
+    fn compass_directions() {
+        for input in ["R 4\nN 4", "R 4\nNE 1"] {
+            let e = RopeBridge::new(input).err().unwrap();
+            assert_eq!(e.to_string(), "line 2, column 1: expected U, D, L or R");
+            assert_eq!(RopeBridge::stream(input.as_bytes()), Err(e));
+        }
+    }
+
     #[test]
     fn part2_1() {
         let res = RopeBridge::new_unwrap(EXAMPLE_INPUT);
@@ -1,21 +1,40 @@
-use std::io::{self, Write};
+use crate::utils::parse::{self, lines, literal, signed, ParseResult, Parser};
+use crate::utils::{stream_lines, ParseError, Section};
+use std::io::{BufRead, Write};
 
-struct CPU<T: Iterator<Item=Instruction>>{
+struct Cpu<T: Iterator<Item=Instruction>>{
     x: isize,
     current_operation: Option<isize>,
     instructions: T,
+    cycle: isize,
+    /// The sum of the signal strengths during the 20th, 60th, 100th... cycles
+    signal_strength: isize,
 }
 
-impl<T: Iterator<Item=Instruction>> CPU<T> {
+impl<T: Iterator<Item=Instruction>> Cpu<T> {
     fn new(instructions: T) -> Self {
         Self {
             x: 1,
             current_operation: None,
             instructions,
+            cycle: 0,
+            signal_strength: 0,
         }
     }
 
     fn tick(&mut self) -> isize {
+        let x = self.step();
+        self.cycle += 1;
+
+        if self.cycle % 40 == 20 {
+            self.signal_strength += self.cycle * x;
+        }
+
+        x
+    }
+
+    /// The value of `x` during the next cycle
+    fn step(&mut self) -> isize {
         if let Some(val) = self.current_operation.take() {
             let old = self.x;
             self.x += val;
@@ -34,13 +53,13 @@ impl<T: Iterator<Item=Instruction>> CPU<T> {
             let position = self.tick();
 
             if (position..(position+3)).contains(&current_position) {
-                output.write(b"#").ok();
+                output.write_all(b"#").ok();
             } else {
-                output.write(b".").ok();
+                output.write_all(b".").ok();
             }
         }
 
-        output.write(b"\n").ok();
+        output.write_all(b"\n").ok();
     }
 }
 
@@ -50,57 +69,94 @@ enum Instruction {
     Addx(isize),
 }
 
+fn instruction(input: &str) -> ParseResult<'_, Instruction> {
+    let noop = literal("noop").map(|_| Instruction::Noop);
+    let addx = literal("addx ").then(signed).map(Instruction::Addx);
+    noop.or(addx).parse(input)
+}
+
 #[derive(Default)]
 pub struct CathodeRayTube {
     instructions: Vec<Instruction>
 }
 
-impl crate::AdventOfCode<isize> for CathodeRayTube {
+impl CathodeRayTube {
+    fn screen(&self) -> String {
+        let mut cpu = Cpu::new(self.instructions.iter().copied());
+        let mut screen = Vec::new();
+
+        for _ in 0..6 {
+            cpu.draw_row(&mut screen);
+        }
+
+        String::from_utf8(screen).unwrap()
+    }
+}
+
+// part 2 returns the screen instead of printing it, both parts share the
+// output type so part 1 is a `String` too
+impl crate::AdventOfCode<'_, String> for CathodeRayTube {
     const TITLE: &'static str = "Cathode-Ray Tube";
     const DAY: u8 = 10;
+    const VERSION: u32 = 2;
+
+    fn new(input: &str) -> Result<Self, ParseError> {
+        let instructions = parse::all(input, lines(instruction))?;
+        Ok(Self { instructions })
+    }
+
+    fn part1(&self) -> String {
+        let mut cpu = Cpu::new(self.instructions.iter().copied());
 
-    fn new(input: &str) -> Option<Self> {
-        let mut res = Self::default();
-
-        for line in input.lines() {
-            let mut iter = line.split_whitespace();
-            let instruction = match iter.next()? {
-                "noop" => Instruction::Noop,
-                "addx" => {
-                    let val = iter.next()?.parse::<isize>().ok()?;
-                    Instruction::Addx(val)
-                }
-                _ => return None,
-            };
-
-            res.instructions.push(instruction);
+        for _ in 0..220 {
+            cpu.tick();
         }
 
-        Some(res)
+        cpu.signal_strength.to_string()
     }
 
-    fn part1(&self) -> isize {
-        let mut cpu = CPU::new(self.instructions.iter().copied());
-        let mut res = 0;
-
-        res += 20  * (0..20).map(|_| cpu.tick()).last().unwrap();
-        res += 60  * (0..40).map(|_| cpu.tick()).last().unwrap();
-        res += 100 * (0..40).map(|_| cpu.tick()).last().unwrap();
-        res += 140 * (0..40).map(|_| cpu.tick()).last().unwrap();
-        res += 180 * (0..40).map(|_| cpu.tick()).last().unwrap();
-        res += 220 * (0..40).map(|_| cpu.tick()).last().unwrap();
+    fn part2(&self) -> String {
+        self.screen()
+    }
 
-        res
+    fn visualize(&self) -> Option<String> {
+        Some(self.screen())
     }
+}
+
+impl crate::Streaming<'_, String> for CathodeRayTube {
+    fn stream(reader: impl BufRead) -> Result<[String; 2], ParseError> {
+        let mut error = None;
+        let mut instructions = stream_lines(reader).map_while(|line| {
+            let parsed = line.and_then(|(number, line)| {
+                let section = Section {
+                    text: &line,
+                    line: number,
+                };
+                section.parse(instruction)
+            });
 
-    fn part2(&self) -> isize {
-        let mut cpu = CPU::new(self.instructions.iter().copied());
+            parsed.map_err(|e| error = Some(e)).ok()
+        });
+
+        let mut cpu = Cpu::new(instructions.by_ref());
+        let mut screen = Vec::new();
 
         for _ in 0..6 {
-            cpu.draw_row(io::stdout());
+            cpu.draw_row(&mut screen);
         }
 
-        0
+        let signal_strength = cpu.signal_strength;
+
+        // the lines after the last cycle are checked like `new` does
+        instructions.for_each(drop);
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        let screen = String::from_utf8(screen).unwrap();
+        Ok([signal_strength.to_string(), screen])
     }
 }
 
@@ -259,12 +315,30 @@ noop"#;
     #[test]
     fn part1() {
         let res = CathodeRayTube::new_unwrap(EXAMPLE_INPUT);
-        assert_eq!(res.part1(), 13140);
+        assert_eq!(res.part1(), "13140");
+    }
+
+    #[test]
+    fn stream() {
+        let [part1, part2] = CathodeRayTube::stream(EXAMPLE_INPUT.as_bytes()).unwrap();
+        assert_eq!(part1, "13140");
+        assert_eq!(part2, CathodeRayTube::new_unwrap(EXAMPLE_INPUT).part2());
+
+        let input = format!("{EXAMPLE_INPUT}\nnoop\njump 3");
+        let e = CathodeRayTube::new(&input).err().unwrap();
+        assert_eq!(CathodeRayTube::stream(input.as_bytes()), Err(e));
     }
 
     #[test]
     fn part2_1() {
         let res = CathodeRayTube::new_unwrap(EXAMPLE_INPUT);
-        assert_eq!(res.part2(), 0);
+        let screen = r#"##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....
+"#;
+        assert_eq!(res.part2(), screen);
     }
 }
@@ -0,0 +1,91 @@
+//! Clean up of the inputs before they are parsed, so a file saved with a
+//! byte order mark, Windows line breaks or stray spaces still works
+//!
+//! None of the steps adds or removes a line break in the middle of the input,
+//! so the line of a `ParseError` is the same in the normalized and the
+//! original file. Only the line is kept: the column is the one in the
+//! normalized line, which no longer starts with a byte order mark.
+
+/// How much of the input the runner cleans up before `new()` gets it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Normalize {
+    /// Leave the input as it is
+    Off,
+    /// Strip a byte order mark and convert `\r\n` line breaks to `\n`
+    LineBreaks,
+    /// `LineBreaks`, and trim the whitespace at the end of every line and of the input
+    Full,
+}
+
+pub fn normalize(input: &mut String, mode: Normalize) {
+    if mode == Normalize::Off {
+        return;
+    }
+
+    if input.starts_with('\u{feff}') {
+        input.remove(0);
+    }
+
+    match mode {
+        Normalize::Off => unreachable!(),
+        Normalize::LineBreaks => {
+            if input.contains('\r') {
+                *input = input.replace("\r\n", "\n");
+            }
+        }
+        Normalize::Full => {
+            let mut res = String::with_capacity(input.len());
+
+            for line in input.lines() {
+                res.push_str(line.trim_end());
+                res.push('\n');
+            }
+
+            res.truncate(res.trim_end().len());
+            *input = res;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse::{self, lines, unsigned};
+
+    const INPUT: &str = "\u{feff}1 \r\n  2\r\n\r\n3\t\r\n\r\n";
+
+    fn normalized(input: &str, mode: Normalize) -> String {
+        let mut input = input.to_owned();
+        normalize(&mut input, mode);
+        input
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(normalized(INPUT, Normalize::Off), INPUT);
+        assert_eq!(
+            normalized(INPUT, Normalize::LineBreaks),
+            "1 \n  2\n\n3\t\n\n"
+        );
+        assert_eq!(normalized(INPUT, Normalize::Full), "1\n  2\n\n3");
+    }
+
+    #[test]
+    fn error_after_bom() {
+        let input = normalized("\u{feff}1x\r\n2\r\n", Normalize::Full);
+        let e = parse::all(&input, lines(unsigned::<u8>)).unwrap_err();
+        assert_eq!(e.position, Some((1, 2)));
+    }
+
+    #[test]
+    fn same_lines() {
+        let input = "a\r\n\r\nb c  \r\nd";
+        let normalized = normalized(input, Normalize::Full);
+        let lines = |s: &str| {
+            s.lines()
+                .map(|v| v.trim_end().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lines(&normalized), lines(input));
+    }
+}
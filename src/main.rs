@@ -1,3 +1,6 @@
+#[macro_use]
+mod profile;
+
 mod day01;
 mod day02;
 mod day03;
@@ -9,175 +12,657 @@ mod day08;
 mod day09;
 mod day10;
 
+mod alloc;
+mod cache;
+mod cli;
+mod config;
+mod dashboard;
+mod input;
+mod output;
+mod report;
 pub mod utils;
 pub use utils::*;
 
-use humantime::format_duration;
-use owo_colors::OwoColorize as _;
-use std::env;
+use alloc::AllocStats;
+use cache::{Cache, Key};
+use cli::{Args, Query, USAGE};
+use config::Config;
+pub use input::Normalize;
+use output::{OutputMode, Printer};
+use std::collections::BTreeMap;
 use std::fmt::Display;
-use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
-pub trait AdventOfCode<Output = u64>
+/// A solution of a day, the parsed input can borrow from the input text for `'a`
+pub trait AdventOfCode<'a, Output = u64>
 where
     Output: Display,
 {
     const TITLE: &'static str;
     const DAY: u8;
-
-    fn new(input: &str) -> Option<Self>
+    /// How the runner cleans up the input, turn it off for whitespace-significant inputs
+    const NORMALIZE: Normalize = Normalize::Full;
+    /// Part of the key of the cached answers, bump it when a change can alter the answers
+    /// or which inputs are accepted, so that a warm cache does not keep the old ones
+    const VERSION: u32 = 1;
+    /// The names `query` answers to, for the `query` command
+    const QUERIES: &'static [&'static str] = &[];
+
+    fn new(input: &'a str) -> Result<Self, ParseError>
     where
         Self: Sized;
 
     fn part1(&self) -> Output;
     fn part2(&self) -> Output;
 
-    fn new_unwrap(input: &str) -> Self
+    /// A text rendering of the puzzle state, embedded into the HTML report
+    fn visualize(&self) -> Option<String> {
+        None
+    }
+
+    /// An extra report about the puzzle, one of `QUERIES`, with its own arguments.
+    /// It gets the input text, as it may not read it the way `new` does
+    fn query(_input: &'a str, name: &str, _args: &[String]) -> Result<String, String>
+    where
+        Self: Sized,
+    {
+        Err(format!("unknown query `{name}`"))
+    }
+
+    /// Reads the input of a query with `new`, or another constructor of the day,
+    /// the error is the one the runner prints
+    fn new_query(
+        input: &'a str,
+        new: impl FnOnce(&'a str) -> Result<Self, ParseError>,
+    ) -> Result<Self, String>
+    where
+        Self: Sized,
+    {
+        new(input).map_err(|e| format!("Cannot parse the input: {e}"))
+    }
+
+    fn new_unwrap(input: &'a str) -> Self
     where
         Self: Sized,
     {
         match Self::new(input) {
-            Some(res) => res,
-            None => panic!(
-                "Cannot parse the input of `{}` (day {})",
+            Ok(res) => res,
+            Err(e) => panic!(
+                "Cannot parse the input of `{}` (day {}): {e}",
                 Self::TITLE,
                 Self::DAY
             ),
         }
     }
 
-    fn exec() -> Option<Timing>
+    /// Reads the input into `input`, which outlives the parsed day borrowing from it, and runs the day
+    fn exec(
+        input: &'a mut Option<String>,
+        options: &RunOptions,
+        progress: &mut dyn FnMut(Progress),
+    ) -> Option<Timing>
     where
-        Self: Sized,
+        Self: Sized + Sync,
+        Output: Send,
     {
-        let title = format!("DAY {} - {}", Self::DAY, Self::TITLE);
-        println!("{}", title.bold());
+        progress(Progress::Started(Self::TITLE));
 
-        let input_name = format!("day{:02}.txt", Self::DAY);
-        let input_path = Path::new("./input/").join(&input_name);
+        let Some(input) = read_input(input, options, Self::DAY, Self::NORMALIZE) else {
+            progress(Progress::MissingInput);
+            return None;
+        };
 
-        let input = fs::read_to_string(input_path).ok()?;
+        let key = Key::new(Self::DAY, Self::VERSION, input);
+        let cached = options.cache.as_ref().and_then(|cache| cache.get(&key));
+
+        if let Some([part1, part2]) = cached {
+            progress(Progress::Cached);
+            progress(Progress::Solved(1, part1.clone(), Duration::ZERO));
+            progress(Progress::Solved(2, part2.clone(), Duration::ZERO));
+
+            return Some(Timing {
+                title: Self::TITLE,
+                parsing: Duration::ZERO,
+                part1: Duration::ZERO,
+                part2: Duration::ZERO,
+                answers: [part1, part2],
+                allocations: Default::default(),
+                spans: Default::default(),
+                visualization: None,
+            });
+        }
 
-        let (f, parse_time) = time(|| Self::new(&input));
+        let ((f, parse_time, parse_allocs), parse_spans) =
+            profile::collect(|| measure(|| Self::new(input)));
 
         let f = match f {
-            Some(f) => f,
-            None => {
-                println!("Cannot parse the input");
+            Ok(f) => f,
+            Err(e) => {
+                progress(Progress::ParseFailed(e));
                 return None;
             }
         };
 
-        println!(
-            "Input parsed in {}",
-            format_duration(parse_time).bright_magenta()
-        );
-
-        let (res, part1_time) = time(|| f.part1());
-        println!(
-            "Part 1: {} ({}) (total {})",
-            res,
-            format_duration(part1_time).cyan(),
-            format_duration(part1_time + parse_time).bright_cyan(),
-        );
-
-        let (res, part2_time) = time(|| f.part2());
-        println!(
-            "Part 2: {} ({}) (total {})",
-            res,
-            format_duration(part2_time).cyan(),
-            format_duration(part2_time + parse_time).bright_cyan(),
-        );
+        progress(Progress::Parsed(parse_time));
+
+        let (
+            ((part1, part1_time, part1_allocs), part1_spans),
+            ((part2, part2_time, part2_allocs), part2_spans),
+        ) = thread::scope(|s| {
+            let handle = options
+                .parallel_parts
+                .then(|| s.spawn(|| profile::collect(|| measure(|| f.part2()))));
+
+            let ((part1, part1_time, part1_allocs), part1_spans) =
+                profile::collect(|| measure(|| f.part1()));
+            let part1 = part1.to_string();
+            progress(Progress::Solved(1, part1.clone(), part1_time));
+
+            let ((part2, part2_time, part2_allocs), part2_spans) = match handle {
+                Some(handle) => handle.join().unwrap_or_else(|e| panic::resume_unwind(e)),
+                None => profile::collect(|| measure(|| f.part2())),
+            };
+            let part2 = part2.to_string();
+            progress(Progress::Solved(2, part2.clone(), part2_time));
+
+            (
+                ((part1, part1_time, part1_allocs), part1_spans),
+                ((part2, part2_time, part2_allocs), part2_spans),
+            )
+        });
+
+        if let Some(cache) = &options.cache {
+            cache.insert(key, [part1.clone(), part2.clone()]);
+        }
 
         Some(Timing {
             title: Self::TITLE,
             parsing: parse_time,
             part1: part1_time,
             part2: part2_time,
+            answers: [part1, part2],
+            allocations: [parse_allocs, part1_allocs, part2_allocs],
+            spans: [parse_spans, part1_spans, part2_spans],
+            visualization: f.visualize(),
         })
     }
+
+    /// Reads the input like `exec` does and runs the query `name` on it
+    fn exec_query(
+        input: &'a mut Option<String>,
+        options: &RunOptions,
+        name: &str,
+        args: &[String],
+    ) -> Result<String, String>
+    where
+        Self: Sized,
+    {
+        if !Self::QUERIES.contains(&name) {
+            return match Self::QUERIES {
+                [] => Err(format!("day {} has no queries", Self::DAY)),
+                list => Err(format!(
+                    "day {} has no query `{name}`, only {}",
+                    Self::DAY,
+                    list.join(", ")
+                )),
+            };
+        }
+
+        let input = read_input(input, options, Self::DAY, Self::NORMALIZE)
+            .ok_or("Cannot read the input file")?;
+
+        Self::query(input, name, args)
+    }
+}
+
+/// Reads and normalizes the input of `day` into `input`, which the parsed day can borrow from
+fn read_input<'a>(
+    input: &'a mut Option<String>,
+    options: &RunOptions,
+    day: u8,
+    mode: Normalize,
+) -> Option<&'a String> {
+    let input_path = options.input_dir.join(format!("day{day:02}.txt"));
+    *input = fs::read_to_string(input_path).ok();
+
+    let input = input.as_mut()?;
+    input::normalize(input, mode);
+    Some(input)
+}
+
+/// A day that finds both answers in a single pass over the input, without
+/// keeping it in memory, so it can run on inputs that do not fit into a `String`
+pub trait Streaming<'a, Output = u64>: AdventOfCode<'a, Output>
+where
+    Output: Display,
+{
+    fn stream(reader: impl BufRead) -> Result<[Output; 2], ParseError>;
+
+    fn exec_stream(options: &RunOptions, progress: &mut dyn FnMut(Progress)) -> Option<Timing>
+    where
+        Self: Sized,
+    {
+        progress(Progress::Started(Self::TITLE));
+
+        let input_name = format!("day{:02}.txt", Self::DAY);
+        let input_path = options.input_dir.join(&input_name);
+
+        let Ok(file) = File::open(input_path) else {
+            progress(Progress::MissingInput);
+            return None;
+        };
+
+        let (answers, time, allocs) = measure(|| Self::stream(BufReader::new(file)));
+
+        let [part1, part2] = match answers {
+            Ok(answers) => answers.map(|v| v.to_string()),
+            Err(e) => {
+                progress(Progress::ParseFailed(e));
+                return None;
+            }
+        };
+
+        // reading and solving is the same pass, so all of it counts as parsing
+        progress(Progress::Parsed(time));
+        progress(Progress::Solved(1, part1.clone(), Duration::ZERO));
+        progress(Progress::Solved(2, part2.clone(), Duration::ZERO));
+
+        Some(Timing {
+            title: Self::TITLE,
+            parsing: time,
+            part1: Duration::ZERO,
+            part2: Duration::ZERO,
+            answers: [part1, part2],
+            allocations: [allocs, AllocStats::default(), AllocStats::default()],
+            spans: Default::default(),
+            visualization: None,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct RunOptions {
+    pub input_dir: PathBuf,
+    /// Give up on a day that takes longer than this
+    pub timeout: Option<Duration>,
+    /// Solve part 1 and part 2 at the same time
+    pub parallel_parts: bool,
+    /// Read the input line by line for the days that implement `Streaming`
+    pub stream: bool,
+    /// Where the answers are looked up before solving, and stored after
+    pub cache: Option<Arc<Cache>>,
+}
+
+impl RunOptions {
+    fn new(config: &Config) -> Self {
+        Self {
+            input_dir: config.input_dir.clone(),
+            timeout: config.timeout,
+            parallel_parts: false,
+            stream: false,
+            cache: config
+                .cache
+                .enabled
+                .then(|| Arc::new(Cache::load(&config.cache.file))),
+        }
+    }
+
+    /// The options to run the days of `args` with, concurrent parts would skew
+    /// the timings of a benchmark
+    fn for_days(args: &Args, config: &Config) -> Self {
+        Self {
+            parallel_parts: args.parallel_parts && !args.benchmark,
+            stream: args.stream,
+            ..Self::new(config)
+        }
+    }
+}
+
+/// How many days run at the same time, concurrent days would skew the timings of a benchmark
+fn jobs(args: &Args) -> usize {
+    match args.benchmark {
+        true => 1,
+        false => args.jobs,
+    }
+}
+
+/// How many times each day runs, only a benchmark runs them more than once
+fn samples(args: &Args, config: &Config) -> usize {
+    match args.benchmark {
+        true => config.benchmark.samples,
+        false => 1,
+    }
+}
+
+/// What happened while running a day, reported as soon as it happens
+#[derive(Clone, Debug)]
+pub enum Progress {
+    Started(&'static str),
+    NoSolution,
+    InvalidDay,
+    MissingInput,
+    ParseFailed(ParseError),
+    TimedOut(Duration),
+    Parsed(Duration),
+    /// The answers were found in the cache, the day was not solved
+    Cached,
+    Solved(u8, String, Duration),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Timing {
     title: &'static str,
     parsing: Duration,
     part1: Duration,
     part2: Duration,
+    answers: [String; 2],
+    /// Allocations made while parsing, solving part 1 and solving part 2
+    allocations: [AllocStats; 3],
+    /// The `span!` timings of the same phases, empty without the `profiling` feature
+    spans: [Vec<profile::Tree>; 3],
+    visualization: Option<String>,
 }
 
-fn time<T>(f: impl Fn() -> T) -> (T, Duration) {
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration, AllocStats) {
+    let allocs = AllocStats::current();
     let start = Instant::now();
     let res = f();
     let duration = start.elapsed();
 
-    (res, duration)
+    (res, duration, AllocStats::current() - allocs)
+}
+
+fn run(day: u8, options: &RunOptions, progress: &mut dyn FnMut(Progress)) -> Option<Timing> {
+    let Some(timeout) = options.timeout else {
+        return solve(day, options, progress);
+    };
+
+    // the day runs on its own thread, which is left behind when it times out
+    let (tx, rx) = mpsc::channel();
+    let thread_options = options.clone();
+    let handle = thread::spawn(move || {
+        solve(day, &thread_options, &mut |v| {
+            tx.send(v).ok();
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(v) => progress(v),
+            Err(RecvTimeoutError::Disconnected) => {
+                return handle.join().unwrap_or_else(|e| panic::resume_unwind(e));
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                progress(Progress::TimedOut(timeout));
+                return None;
+            }
+        }
+    }
 }
 
-fn run(day: u8) -> Option<Timing> {
+#[allow(clippy::zero_prefixed_literal)]
+fn solve(day: u8, options: &RunOptions, progress: &mut dyn FnMut(Progress)) -> Option<Timing> {
+    let mut input = None;
+
     match day {
-        01 => day01::CalorieCounting::exec(),
-        02 => day02::RockPaperScissors::exec(),
-        03 => day03::RucksackReorganization::exec(),
-        04 => day04::CampCleanup::exec(),
-        05 => day05::SupplyStacks::exec(),
-        06 => day06::TuningTrouble::exec(),
-        07 => day07::NoSpaceLeftOnDevice::exec(),
-        08 => day08::TreetopTreeHouse::exec(),
-        09 => day09::RopeBridge::exec(),
-        10 => day10::CathodeRayTube::exec(),
+        01 if options.stream => day01::CalorieCounting::exec_stream(options, progress),
+        02 if options.stream => day02::RockPaperScissors::exec_stream(options, progress),
+        03 if options.stream => day03::RucksackReorganization::exec_stream(options, progress),
+        04 if options.stream => day04::CampCleanup::exec_stream(options, progress),
+        06 if options.stream => day06::TuningTrouble::exec_stream(options, progress),
+        09 if options.stream => day09::RopeBridge::exec_stream(options, progress),
+        10 if options.stream => day10::CathodeRayTube::exec_stream(options, progress),
+        01 => day01::CalorieCounting::exec(&mut input, options, progress),
+        02 => day02::RockPaperScissors::exec(&mut input, options, progress),
+        03 => day03::RucksackReorganization::exec(&mut input, options, progress),
+        04 => day04::CampCleanup::exec(&mut input, options, progress),
+        05 => day05::SupplyStacks::exec(&mut input, options, progress),
+        06 => day06::TuningTrouble::exec(&mut input, options, progress),
+        07 => day07::NoSpaceLeftOnDevice::exec(&mut input, options, progress),
+        08 => day08::TreetopTreeHouse::exec(&mut input, options, progress),
+        09 => day09::RopeBridge::exec(&mut input, options, progress),
+        10 => day10::CathodeRayTube::exec(&mut input, options, progress),
         26.. => {
-            println!("{day} is not a valid day for AdventOfCode");
+            progress(Progress::InvalidDay);
             None
         }
         _ => {
-            println!("There is no solution for day {day} yet");
+            progress(Progress::NoSolution);
             None
         }
     }
 }
 
+#[allow(clippy::zero_prefixed_literal)]
+fn solve_query(query: &Query, options: &RunOptions) -> Result<String, String> {
+    let mut input = None;
+    let (name, args) = (query.name.as_str(), query.args.as_slice());
+
+    match query.day {
+        01 => day01::CalorieCounting::exec_query(&mut input, options, name, args),
+        02 => day02::RockPaperScissors::exec_query(&mut input, options, name, args),
+        03 => day03::RucksackReorganization::exec_query(&mut input, options, name, args),
+        04 => day04::CampCleanup::exec_query(&mut input, options, name, args),
+        05 => day05::SupplyStacks::exec_query(&mut input, options, name, args),
+        06 => day06::TuningTrouble::exec_query(&mut input, options, name, args),
+        07 => day07::NoSpaceLeftOnDevice::exec_query(&mut input, options, name, args),
+        08 => day08::TreetopTreeHouse::exec_query(&mut input, options, name, args),
+        09 => day09::RopeBridge::exec_query(&mut input, options, name, args),
+        10 => day10::CathodeRayTube::exec_query(&mut input, options, name, args),
+        26.. => Err(format!("{} is not a valid day for AdventOfCode", query.day)),
+        _ => Err(format!("There is no solution for day {} yet", query.day)),
+    }
+}
+
 fn main() {
-    if env::args().any(|v| v == "-l" || v == "--log") {
-        setup_logger();
+    let args = match Args::from_env() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    if args.help {
+        println!("{USAGE}");
+        return;
     }
 
-    let mut days = env::args()
-        .skip(1)
-        .filter_map(|v| v.parse::<u8>().ok())
-        .collect::<Vec<_>>();
+    let config = match Config::resolve(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(2);
+        }
+    };
+
+    if let Some(query) = &args.query {
+        match solve_query(query, &RunOptions::new(&config)) {
+            Ok(report) => print!("{report}"),
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        }
 
-    if days.is_empty() {
-        days = (1..=25).collect();
+        return;
     }
 
-    let mut timings: [Option<Timing>; 25] = [None; 25];
-    let mut iter = days.into_iter().peekable();
+    if args.clear_cache {
+        match Cache::clear(&config.cache.file) {
+            Ok(true) => println!("Removed the cache `{}`", config.cache.file.display()),
+            Ok(false) => println!("There is no cache `{}`", config.cache.file.display()),
+            Err(e) => {
+                eprintln!("Cannot remove `{}`: {e}", config.cache.file.display());
+                process::exit(1);
+            }
+        }
 
-    while let Some(day) = iter.next() {
-        let timing = run(day);
-        timings[day as usize - 1] = timing;
+        return;
+    }
 
-        if timing.is_some() && iter.peek().is_some() {
-            println!("");
+    let (timings, finished) = if args.dashboard && io::stdout().is_terminal() {
+        dashboard::run(&args, &config).unwrap()
+    } else {
+        if config.log.enabled {
+            logger()
+                .chain(fs::File::create(&config.log.file).unwrap())
+                .apply()
+                .unwrap();
         }
+
+        (run_plain(&args, &config), true)
+    };
+
+    if args.benchmark && !finished {
+        eprintln!("The run was stopped before every day finished, the benchmark is not written");
+    } else if args.benchmark {
+        print_benchmark(&config.benchmark.output, config.year, &timings).unwrap();
     }
 
-    if env::args().any(|v| v == "--benchmark" || v == "-b") {
-        print_benchmark(timings).unwrap();
+    if args.report {
+        report::write_html(&config.report.output, config.year, &timings).unwrap();
     }
 }
 
-fn setup_logger() -> () {
+fn run_plain(args: &Args, config: &Config) -> Vec<Option<Timing>> {
+    let jobs = jobs(args);
+    let samples = samples(args, config);
+    let options = RunOptions::for_days(args, config);
+
+    let mut timings: Vec<Option<Timing>> = vec![None; 25];
+    let mut iter = args.days.iter().copied().peekable();
+    let color = config.color.enabled();
+    let printer = |day: u8| Printer::new(day, args.output, color);
+    let mut finish = |day: u8, timing: Option<Timing>, has_next: bool| {
+        let Some(timing) = timing else {
+            return;
+        };
+
+        if args.output == OutputMode::Normal {
+            print!("{}", profile::report(&timing.spans));
+
+            if has_next {
+                println!();
+            }
+        }
+
+        timings[day as usize - 1] = Some(timing);
+    };
+
+    if jobs <= 1 {
+        while let Some(day) = iter.next() {
+            let mut printer = printer(day);
+            let mut timing = run(day, &options, &mut |v| printer.print(v));
+
+            if samples > 1 {
+                timing = timing.map(|v| sample(day, &options, v, samples));
+            }
+
+            finish(day, timing, iter.peek().is_some());
+        }
+    } else {
+        run_parallel(&args.days, jobs, &options, |idx, events, timing| {
+            let day = args.days[idx];
+            let mut printer = printer(day);
+            events.into_iter().for_each(|v| printer.print(v));
+            finish(day, timing, idx + 1 < args.days.len());
+        });
+    }
+
+    timings
+}
+
+/// Runs an already solved day until there are `samples` timings of it, and
+/// keeps the median of each phase
+fn sample(day: u8, options: &RunOptions, first: Timing, samples: usize) -> Timing {
+    let mut timings = vec![first.clone()];
+
+    for _ in 1..samples {
+        match run(day, options, &mut |_| {}) {
+            Some(timing) => timings.push(timing),
+            None => break,
+        }
+    }
+
+    let median = |phase: fn(&Timing) -> Duration| {
+        let mut list: Vec<Duration> = timings.iter().map(phase).collect();
+        list.sort_unstable();
+        list[list.len() / 2]
+    };
+
+    Timing {
+        parsing: median(|v| v.parsing),
+        part1: median(|v| v.part1),
+        part2: median(|v| v.part2),
+        ..first
+    }
+}
+
+/// Runs the days on `jobs` threads, but hands the results to `finish` in the
+/// same order as the days were given
+fn run_parallel(
+    days: &[u8],
+    jobs: usize,
+    options: &RunOptions,
+    mut finish: impl FnMut(usize, Vec<Progress>, Option<Timing>),
+) {
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|s| {
+        for _ in 0..jobs.min(days.len()) {
+            let tx = tx.clone();
+            let next = &next;
+
+            s.spawn(move || loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(&day) = days.get(idx) else {
+                    break;
+                };
+
+                let mut events = Vec::new();
+                let timing = panic::catch_unwind(AssertUnwindSafe(|| {
+                    run(day, options, &mut |v| events.push(v))
+                }));
+
+                tx.send((idx, events, timing)).ok();
+            });
+        }
+
+        drop(tx);
+
+        let mut finished = BTreeMap::new();
+        let mut current = 0;
+
+        for (idx, events, timing) in rx {
+            finished.insert(idx, (events, timing));
+
+            while let Some((events, timing)) = finished.remove(&current) {
+                // a panic is raised only after every day before it was printed
+                let timing = timing.unwrap_or_else(|e| panic::resume_unwind(e));
+                finish(current, events, timing);
+                current += 1;
+            }
+        }
+    });
+}
+
+fn logger() -> fern::Dispatch {
     fern::Dispatch::new()
         .format(|out, msg, record| out.finish(format_args!("{} - {}", record.level(), msg)))
         .level(log::LevelFilter::Debug)
-        .chain(fs::File::create("output.log").unwrap())
-        .apply()
-        .unwrap()
 }
 
 fn display_benchmark_time(time: Duration) -> String {
@@ -188,34 +673,38 @@ fn display_benchmark_time(time: Duration) -> String {
         .collect()
 }
 
-fn print_benchmark(timings: [Option<Timing>; 25]) -> std::io::Result<()> {
-    let mut file = fs::File::create("benchmark.md")?;
+fn print_benchmark(path: &Path, year: u16, timings: &[Option<Timing>]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
 
     writeln!(
         &mut file,
-        "| Day - Name | Parse time | Part 1 | Part 2 | AoC link |"
+        "| Day - Name | Parse time | Parse memory | Part 1 | Part 2 | AoC link |"
     )?;
     writeln!(
         &mut file,
-        "| :--------- | ---------: | -----: | -----: | :------: |"
+        "| :--------- | ---------: | -----------: | -----: | -----: | :------: |"
     )?;
 
     for (timing, day) in timings.iter().zip(1..) {
-        let url = "[🔗](https://adventofcode.com/2022/day/{day})";
+        let url = format!("[🔗](https://adventofcode.com/{year}/day/{day})");
 
         if let Some(timing) = timing {
+            let parse_allocs = timing.allocations[0];
+
             writeln!(
                 &mut file,
-                "| [{:02} - {}](/src/day{:02}.rs) | {} | {} | {} | {url} |",
+                "| [{:02} - {}](/src/day{:02}.rs) | {} | {} ({} allocs) | {} | {} | {url} |",
                 day,
                 timing.title,
                 day,
                 display_benchmark_time(timing.parsing),
+                report::display_bytes(parse_allocs.bytes),
+                parse_allocs.allocations,
                 display_benchmark_time(timing.part1),
                 display_benchmark_time(timing.part2),
             )?;
         } else {
-            writeln!(&mut file, "| {:02} - | - | - | - | {url} |", day)?;
+            writeln!(&mut file, "| {:02} - | - | - | - | - | {url} |", day)?;
         }
     }
 
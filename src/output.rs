@@ -0,0 +1,196 @@
+//! How the progress of a day is printed in the terminal
+
+use crate::Progress;
+use humantime::format_duration;
+use owo_colors::{OwoColorize as _, Style};
+use std::env;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// `auto` colors only a terminal, and only when `NO_COLOR` is not set
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("`{s}` is not one of auto, always or never")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OutputMode {
+    /// Titles, answers and timings
+    #[default]
+    Normal,
+    /// Only the answers, one per line
+    Quiet,
+    /// `day part answer` for every answer
+    Answers,
+}
+
+/// An answer on a single line for the `day part answer` format, a line break
+/// is written `\n` and a backslash `\\`
+pub fn escape_answer(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Prints the progress of a single day
+pub struct Printer {
+    day: u8,
+    mode: OutputMode,
+    color: bool,
+    parse_time: Duration,
+    /// The answers come from the cache, so there are no timings to show
+    cached: bool,
+}
+
+impl Printer {
+    pub fn new(day: u8, mode: OutputMode, color: bool) -> Self {
+        Self {
+            day,
+            mode,
+            color,
+            parse_time: Duration::ZERO,
+            cached: false,
+        }
+    }
+
+    fn paint(&self, style: Style) -> Style {
+        match self.color {
+            true => style,
+            false => Style::new(),
+        }
+    }
+
+    /// Problems are part of the normal output, but go to stderr when only
+    /// the answers are printed so they never end up in a piped result
+    fn problem(&self, message: &str) {
+        match self.mode {
+            OutputMode::Normal => println!("{message}"),
+            OutputMode::Quiet | OutputMode::Answers => eprintln!("day {}: {message}", self.day),
+        }
+    }
+
+    pub fn print(&mut self, progress: Progress) {
+        let day = self.day;
+
+        match progress {
+            Progress::Started(title) => {
+                if self.mode == OutputMode::Normal {
+                    let title = format!("DAY {day} - {title}");
+                    println!("{}", title.style(self.paint(Style::new().bold())));
+                }
+            }
+            Progress::NoSolution => {
+                if self.mode == OutputMode::Normal {
+                    println!("There is no solution for day {day} yet");
+                }
+            }
+            Progress::InvalidDay => {
+                self.problem(&format!("{day} is not a valid day for AdventOfCode"))
+            }
+            Progress::MissingInput => self.problem("Cannot read the input file"),
+            Progress::ParseFailed(e) => self.problem(&format!("Cannot parse the input: {e}")),
+            Progress::TimedOut(time) => {
+                self.problem(&format!("Timed out after {}", format_duration(time)))
+            }
+            Progress::Parsed(time) => {
+                self.parse_time = time;
+
+                if self.mode == OutputMode::Normal {
+                    let time = format_duration(time);
+                    println!(
+                        "Input parsed in {}",
+                        time.style(self.paint(Style::new().bright_magenta()))
+                    );
+                }
+            }
+            Progress::Cached => {
+                self.cached = true;
+
+                if self.mode == OutputMode::Normal {
+                    println!("Answers read from the cache");
+                }
+            }
+            Progress::Solved(part, answer, time) => self.print_answer(part, answer, time),
+        }
+    }
+
+    fn print_answer(&self, part: u8, answer: String, time: Duration) {
+        let day = self.day;
+
+        match self.mode {
+            OutputMode::Quiet => println!("{answer}"),
+            OutputMode::Answers => println!("{day} {part} {}", escape_answer(&answer)),
+            OutputMode::Normal => {
+                let time = if self.cached {
+                    let text = "(cached)";
+                    text.style(self.paint(Style::new().cyan())).to_string()
+                } else {
+                    let total = format_duration(time + self.parse_time);
+                    format!(
+                        "({}) (total {})",
+                        format_duration(time).style(self.paint(Style::new().cyan())),
+                        total.style(self.paint(Style::new().bright_cyan())),
+                    )
+                };
+
+                if answer.contains('\n') {
+                    println!("Part {part}: {time}");
+                    print!("{answer}");
+                } else {
+                    println!("Part {part}: {answer} {time}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_choice() {
+        assert_eq!("always".parse(), Ok(ColorChoice::Always));
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert_eq!("auto".parse(), Ok(ColorChoice::Auto));
+        assert!("yes".parse::<ColorChoice>().is_err());
+
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+
+    #[test]
+    fn single_line_answers() {
+        let screen = "#..#\n.##.\n";
+        assert_eq!(escape_answer(screen), "#..#\\n.##.\\n");
+        assert_eq!(escape_answer("a\\nb"), "a\\\\nb");
+        assert_eq!(escape_answer("12"), "12");
+    }
+}
@@ -0,0 +1,197 @@
+//! Nested timings inside the solvers. `let _s = span!("name");` times the
+//! rest of the scope, the runner collects the spans of every phase into a tree.
+//! Without the `profiling` feature a span is `()` and nothing is recorded.
+
+use crate::display_benchmark_time;
+use std::time::Duration;
+
+/// Opens a span that is closed when the returned guard is dropped
+#[cfg(feature = "profiling")]
+macro_rules! span {
+    ($name:expr) => {
+        $crate::profile::Span::enter($name)
+    };
+}
+
+#[cfg(not(feature = "profiling"))]
+macro_rules! span {
+    ($name:expr) => {
+        ()
+    };
+}
+
+/// The time spent in a span, summed over every time it was opened under the same parent
+#[derive(Clone, Debug, Default)]
+pub struct Tree {
+    pub name: &'static str,
+    pub calls: u32,
+    pub total: Duration,
+    pub children: Vec<Tree>,
+}
+
+#[cfg(feature = "profiling")]
+mod recorder {
+    use super::Tree;
+    use std::cell::RefCell;
+    use std::time::Instant;
+
+    #[derive(Default)]
+    pub struct Recorder {
+        /// Holds the top level spans, it is not a span itself
+        root: Tree,
+        /// The open spans, as the index of each one in the children of the one before
+        path: Vec<usize>,
+    }
+
+    impl Recorder {
+        fn current(&mut self) -> &mut Tree {
+            self.path
+                .iter()
+                .fold(&mut self.root, |node, &idx| &mut node.children[idx])
+        }
+    }
+
+    thread_local! {
+        /// Set only while `collect` runs on this thread
+        pub static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+    }
+
+    pub fn take() -> Vec<Tree> {
+        RECORDER.with_borrow_mut(|v| v.take().map(|v| v.root.children).unwrap_or_default())
+    }
+
+    /// Spans have to be closed in the reverse order of opening, which the scopes take care of
+    pub struct Span {
+        start: Option<Instant>,
+    }
+
+    impl Span {
+        pub fn enter(name: &'static str) -> Self {
+            let recording = RECORDER.with_borrow_mut(|recorder| {
+                let Some(recorder) = recorder else {
+                    return false;
+                };
+
+                let current = recorder.current();
+                let idx = match current.children.iter().position(|v| v.name == name) {
+                    Some(idx) => idx,
+                    None => {
+                        current.children.push(Tree {
+                            name,
+                            ..Tree::default()
+                        });
+                        current.children.len() - 1
+                    }
+                };
+
+                recorder.path.push(idx);
+                true
+            });
+
+            Self {
+                start: recording.then(Instant::now),
+            }
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            let Some(start) = self.start else {
+                return;
+            };
+            let elapsed = start.elapsed();
+
+            RECORDER.with_borrow_mut(|recorder| {
+                if let Some(recorder) = recorder {
+                    let current = recorder.current();
+                    current.total += elapsed;
+                    current.calls += 1;
+                    recorder.path.pop();
+                }
+            });
+        }
+    }
+}
+
+#[cfg(feature = "profiling")]
+pub use recorder::Span;
+
+/// Runs `f` and returns the spans it opened on this thread
+#[cfg(feature = "profiling")]
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Vec<Tree>) {
+    recorder::RECORDER.set(Some(Default::default()));
+    let res = f();
+    (res, recorder::take())
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Vec<Tree>) {
+    (f(), Vec::new())
+}
+
+/// An indented tree of the spans of every phase that has some
+pub fn report(spans: &[Vec<Tree>; 3]) -> String {
+    fn write(res: &mut String, trees: &[Tree], depth: usize) {
+        for tree in trees {
+            let calls = match tree.calls {
+                1 => String::new(),
+                n => format!(" ({n} calls)"),
+            };
+
+            res.push_str(&format!(
+                "{:indent$}{} {}{calls}\n",
+                "",
+                tree.name,
+                display_benchmark_time(tree.total).trim_end(),
+                indent = depth * 2,
+            ));
+            write(res, &tree.children, depth + 1);
+        }
+    }
+
+    let mut res = String::new();
+
+    for (trees, phase) in spans.iter().zip(["Parsing", "Part 1", "Part 2"]) {
+        if !trees.is_empty() {
+            res.push_str(&format!("{phase} spans:\n"));
+            write(&mut res, trees, 1);
+        }
+    }
+
+    res
+}
+
+#[cfg(all(test, feature = "profiling"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested() {
+        let ((), trees) = collect(|| {
+            let _outer = span!("outer");
+
+            for _ in 0..3 {
+                let _inner = span!("inner");
+            }
+
+            let _other = span!("other");
+        });
+
+        assert_eq!(trees.len(), 1);
+        assert_eq!((trees[0].name, trees[0].calls), ("outer", 1));
+
+        let children: Vec<_> = trees[0]
+            .children
+            .iter()
+            .map(|v| (v.name, v.calls))
+            .collect();
+        assert_eq!(children, [("inner", 3), ("other", 1)]);
+        assert!(trees[0].total >= trees[0].children[0].total);
+    }
+
+    #[test]
+    fn not_collecting() {
+        drop(span!("ignored"));
+        assert!(collect(|| ()).1.is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! A self-contained static HTML page with the answers, timings, allocations
+//! and visualizations of every solved day.
+
+use crate::alloc::AllocStats;
+use crate::{display_benchmark_time, Timing};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; background: #0f0f23; color: #cccccc; margin: 2em; }
+a { color: #009900; }
+h1, h2 { color: #00cc00; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 4px 8px; border-bottom: 1px solid #333340; text-align: left; vertical-align: top; }
+td.num { text-align: right; white-space: nowrap; }
+code { color: #ffffff; white-space: pre; }
+.bar { display: flex; height: 12px; min-width: 140px; background: #1a1a33; }
+.bar span { display: block; height: 100%; }
+.parse { background: #cc66cc; }
+.part1 { background: #3399cc; }
+.part2 { background: #66cccc; }
+.missing { color: #666666; }
+pre { background: #10101a; border: 1px solid #333340; padding: 8px; line-height: 1; overflow-x: auto; }
+"#;
+
+pub fn write_html(path: &Path, year: u16, timings: &[Option<Timing>]) -> io::Result<()> {
+    fs::write(path, render(year, timings))
+}
+
+fn render(year: u16, timings: &[Option<Timing>]) -> String {
+    let longest = timings
+        .iter()
+        .flatten()
+        .map(|v| v.parsing + v.part1 + v.part2)
+        .max()
+        .unwrap_or_default();
+
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<title>Advent of Code {year}</title>").unwrap();
+    writeln!(html, "<style>{STYLE}</style>").unwrap();
+    writeln!(html, "</head>\n<body>\n<h1>Advent of Code {year}</h1>").unwrap();
+
+    html.push_str("<table>\n<tr><th>Day</th><th>Answers</th><th class=\"num\">Parse</th>");
+    html.push_str("<th class=\"num\">Part 1</th><th class=\"num\">Part 2</th><th>Time</th>");
+    html.push_str("<th class=\"num\">Allocations</th><th>Puzzle</th></tr>\n");
+
+    for (timing, day) in timings.iter().zip(1..) {
+        let puzzle = format!("<a href=\"https://adventofcode.com/{year}/day/{day}\">🔗</a>");
+
+        let Some(timing) = timing else {
+            writeln!(
+                html,
+                "<tr class=\"missing\"><td>{day:02}</td><td colspan=\"6\">-</td><td>{puzzle}</td></tr>"
+            )
+            .unwrap();
+            continue;
+        };
+
+        let allocations = timing
+            .allocations
+            .iter()
+            .fold(AllocStats::default(), |acc, &v| acc + v);
+
+        writeln!(
+            html,
+            "<tr><td><a href=\"src/day{day:02}.rs\">{day:02} - {}</a></td>\
+             <td><code>{}</code><br><code>{}</code></td>\
+             <td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+             <td>{}</td><td class=\"num\">{} ({})</td><td>{puzzle}</td></tr>",
+            escape(timing.title),
+            escape(&timing.answers[0]),
+            escape(&timing.answers[1]),
+            display_benchmark_time(timing.parsing),
+            display_benchmark_time(timing.part1),
+            display_benchmark_time(timing.part2),
+            timing_bar(timing, longest),
+            allocations.allocations,
+            display_bytes(allocations.bytes),
+        )
+        .unwrap();
+    }
+
+    html.push_str("</table>\n");
+
+    for (timing, day) in timings.iter().zip(1..) {
+        let Some(timing) = timing else { continue };
+        let Some(visualization) = &timing.visualization else {
+            continue;
+        };
+
+        writeln!(
+            html,
+            "<h2>Day {day} - {}</h2>\n<pre>{}</pre>",
+            escape(timing.title),
+            escape(visualization)
+        )
+        .unwrap();
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn timing_bar(timing: &Timing, longest: Duration) -> String {
+    let width = |time: Duration| match longest.as_secs_f64() {
+        total if total > 0.0 => time.as_secs_f64() / total * 100.0,
+        _ => 0.0,
+    };
+
+    format!(
+        "<div class=\"bar\"><span class=\"parse\" style=\"width: {:.2}%\"></span>\
+         <span class=\"part1\" style=\"width: {:.2}%\"></span>\
+         <span class=\"part2\" style=\"width: {:.2}%\"></span></div>",
+        width(timing.parsing),
+        width(timing.part1),
+        width(timing.part2),
+    )
+}
+
+pub fn display_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+
+    for ch in s.chars() {
+        match ch {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            _ => res.push(ch),
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(display_bytes(0), "0 B");
+        assert_eq!(display_bytes(1023), "1023 B");
+        assert_eq!(display_bytes(1024), "1.0 KiB");
+        assert_eq!(display_bytes(1536), "1.5 KiB");
+        assert_eq!(display_bytes(5 << 20), "5.0 MiB");
+        assert_eq!(display_bytes(3 << 40), "3072.0 GiB");
+    }
+
+    #[test]
+    fn page() {
+        let timing = Timing {
+            title: "A <title>",
+            parsing: Duration::from_millis(1),
+            part1: Duration::from_millis(2),
+            part2: Duration::from_millis(1),
+            answers: ["1 < 2".to_string(), "done".to_string()],
+            allocations: [AllocStats {
+                allocations: 2,
+                bytes: 2048,
+            }; 3],
+            spans: Default::default(),
+            visualization: Some("#&.".to_string()),
+        };
+        let html = render(2022, &[Some(timing), None]);
+
+        assert!(html.contains("<title>Advent of Code 2022</title>"));
+        assert!(html.contains("01 - A &lt;title&gt;"));
+        assert!(html.contains("<code>1 &lt; 2</code><br><code>done</code>"));
+        assert!(html.contains("6 (6.0 KiB)"));
+        assert!(html.contains("style=\"width: 50.00%\""));
+        assert!(html.contains("<tr class=\"missing\"><td>02</td>"));
+        assert!(html.contains("<h2>Day 1 - A &lt;title&gt;</h2>\n<pre>#&amp;.</pre>"));
+        assert!(!html.contains("Day 2 -"));
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
+/// A set of the indices below `64 * WORDS`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<const WORDS: usize = 1> {
+    words: [u64; WORDS],
+}
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    pub const CAPACITY: usize = 64 * WORDS;
+
+    pub const fn new() -> Self {
+        Self { words: [0; WORDS] }
+    }
+
+    /// Every index below the capacity
+    pub const fn full() -> Self {
+        Self {
+            words: [u64::MAX; WORDS],
+        }
+    }
+
+    /// Panics when `idx` is not below the capacity
+    pub fn insert(&mut self, idx: usize) {
+        self.words[idx / 64] |= 1 << (idx % 64);
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < Self::CAPACITY {
+            self.words[idx / 64] &= !(1 << (idx % 64));
+        }
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        idx < Self::CAPACITY && self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    /// The number of indices in the set
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|v| v.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&v| v == 0)
+    }
+
+    /// The indices in the set, from the smallest
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(idx, &word)| {
+            let mut word = word;
+
+            std::iter::from_fn(move || {
+                let bit = word.trailing_zeros() as usize;
+                word &= word.checked_sub(1)?;
+                Some(idx * 64 + bit)
+            })
+        })
+    }
+}
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut res = Self::new();
+        iter.into_iter().for_each(|idx| res.insert(idx));
+        res
+    }
+}
+
+impl<const WORDS: usize> BitAnd for BitSet<WORDS> {
+    type Output = Self;
+
+    fn bitand(mut self, other: Self) -> Self {
+        self &= other;
+        self
+    }
+}
+
+impl<const WORDS: usize> BitAndAssign for BitSet<WORDS> {
+    fn bitand_assign(&mut self, other: Self) {
+        for (a, b) in self.words.iter_mut().zip(other.words) {
+            *a &= b;
+        }
+    }
+}
+
+impl<const WORDS: usize> BitOr for BitSet<WORDS> {
+    type Output = Self;
+
+    fn bitor(mut self, other: Self) -> Self {
+        self |= other;
+        self
+    }
+}
+
+impl<const WORDS: usize> BitOrAssign for BitSet<WORDS> {
+    fn bitor_assign(&mut self, other: Self) {
+        for (a, b) in self.words.iter_mut().zip(other.words) {
+            *a |= b;
+        }
+    }
+}
+
+impl<const WORDS: usize> fmt::Debug for BitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations() {
+        let a: BitSet<2> = [1, 5, 64, 100].into_iter().collect();
+        let b: BitSet<2> = [5, 63, 100].into_iter().collect();
+
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), [5, 100]);
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), [1, 5, 63, 64, 100]);
+        assert_eq!(a.len(), 4);
+        assert!(a.contains(64) && !a.contains(63) && !a.contains(500));
+        assert!(a.intersection(&BitSet::new()).is_empty());
+        assert_eq!(BitSet::<2>::full().len(), BitSet::<2>::CAPACITY);
+    }
+}
@@ -0,0 +1,232 @@
+use super::{Direction, Vector};
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+/// A rectangle of cells stored row by row, `x` is the column and `y` is the row
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Parses a block of text with one row per line, every line must have the same length
+    pub fn parse(s: &str, mut f: impl FnMut(char) -> Option<T>) -> Option<Self> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for line in s.lines() {
+            let len = cells.len();
+
+            for ch in line.chars() {
+                cells.push(f(ch)?);
+            }
+
+            if *width.get_or_insert(cells.len() - len) != cells.len() - len {
+                return None;
+            }
+
+            height += 1;
+        }
+
+        Some(Self {
+            cells,
+            width: width.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.contains(x, y).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.contains(x, y)
+            .then(|| &mut self.cells[y * self.width + x])
+    }
+
+    /// The position `step` away from `(x, y)`, if it is still inside of the grid
+    pub fn offset(&self, x: usize, y: usize, step: Vector) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(step.x)?;
+        let y = y.checked_add_signed(step.y)?;
+        self.contains(x, y).then_some((x, y))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a chunk size of 0
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let start = if x < self.width { x } else { self.cells.len() };
+        self.cells.iter().skip(start).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// The up to 4 positions that share a side with `(x, y)`
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Direction::CARDINAL
+            .into_iter()
+            .filter_map(move |v| self.offset(x, y, v.vector()))
+    }
+
+    /// The up to 8 positions that share a side or a corner with `(x, y)`
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |v| self.offset(x, y, v.vector()))
+    }
+
+    /// Walks from `(x, y)` in steps of `step` until the edge of the grid,
+    /// the starting position itself is not included
+    pub fn ray(
+        &self,
+        x: usize,
+        y: usize,
+        step: Vector,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let mut current = (x, y);
+
+        std::iter::from_fn(move || {
+            current = self.offset(current.0, current.1, step)?;
+            Some((current, &self[current]))
+        })
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut((usize, usize), &T) -> U) -> Grid<U> {
+        Grid::from_fn(self.width, self.height, |x, y| f((x, y), &self[(x, y)]))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("position outside of the grid")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("position outside of the grid")
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "123\n456";
+
+    fn example() -> Grid<u32> {
+        Grid::parse(EXAMPLE, |v| v.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = example();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(2, 1), Some(&6));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.to_string(), "123\n456\n");
+        assert!(Grid::parse("12\n3", |v| v.to_digit(10)).is_none());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = example();
+        let rows: Vec<&[u32]> = grid.rows().collect();
+        let columns: Vec<Vec<u32>> = grid.columns().map(|v| v.copied().collect()).collect();
+        assert_eq!(rows, [&[1, 2, 3][..], &[4, 5, 6]]);
+        assert_eq!(columns, [[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(grid.column(3).count(), 0);
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = example();
+        let mut four: Vec<_> = grid.neighbors4(0, 0).collect();
+        let mut eight: Vec<_> = grid.neighbors8(1, 0).collect();
+        four.sort_unstable();
+        eight.sort_unstable();
+        assert_eq!(four, [(0, 1), (1, 0)]);
+        assert_eq!(eight, [(0, 0), (0, 1), (1, 1), (2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn ray() {
+        let grid = example();
+        let cells: Vec<u32> = grid.ray(0, 0, Vector::new(1, 0)).map(|(_, &v)| v).collect();
+        assert_eq!(cells, [2, 3]);
+        assert_eq!(grid.ray(2, 1, Vector::new(1, 1)).count(), 0);
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+
+/// The integers from `from` to `to`, both included, so an interval is never empty
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub from: u64,
+    pub to: u64,
+}
+
+impl Interval {
+    /// `None` when `to` is before `from`
+    pub fn new(from: u64, to: u64) -> Option<Self> {
+        (from <= to).then_some(Self { from, to })
+    }
+
+    /// How many integers it has, a `u128` as 0 to `u64::MAX` has one more than `u64::MAX`
+    #[allow(clippy::len_without_is_empty)] // never empty
+    pub fn len(&self) -> u128 {
+        (self.to - self.from) as u128 + 1
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.from <= other.from && self.to >= other.to
+    }
+
+    pub fn contains_value(&self, value: u64) -> bool {
+        self.from <= value && value <= self.to
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.from <= other.to && other.from <= self.to
+    }
+
+    /// Overlapping, or one starts right after the other
+    fn touches(&self, other: &Self) -> bool {
+        self.from <= other.to.saturating_add(1) && other.from <= self.to.saturating_add(1)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.from.max(other.from), self.to.min(other.to))
+    }
+
+    /// `None` when there is a gap between the two, which would leave two intervals
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        self.touches(other).then(|| Self {
+            from: self.from.min(other.from),
+            to: self.to.max(other.to),
+        })
+    }
+
+    /// The parts before and after `other`, either can be missing
+    pub fn difference(&self, other: &Self) -> [Option<Self>; 2] {
+        if !self.overlaps(other) {
+            return [Some(*self), None];
+        }
+
+        let before = other
+            .from
+            .checked_sub(1)
+            .and_then(|to| Self::new(self.from, to));
+        let after = other
+            .to
+            .checked_add(1)
+            .and_then(|from| Self::new(from, self.to));
+
+        [before, after]
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.from, self.to)
+    }
+}
+
+/// The same integers as `intervals`, as the fewest sorted intervals
+pub fn merge(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.sort_unstable();
+
+    let mut res: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        if let Some(last) = res.last_mut() {
+            if let Some(union) = last.union(&interval) {
+                *last = union;
+                continue;
+            }
+        }
+
+        res.push(interval);
+    }
+
+    res
+}
+
+/// A set of integers, kept as sorted intervals with a gap between each two
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many integers are in the set
+    pub fn coverage(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        let idx = self.intervals.partition_point(|v| v.to < value);
+        self.intervals
+            .get(idx)
+            .is_some_and(|v| v.contains_value(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        // the intervals that touch the new one are all between these two
+        let start = self
+            .intervals
+            .partition_point(|v| v.to.saturating_add(1) < interval.from);
+        let end = self
+            .intervals
+            .partition_point(|v| v.from <= interval.to.saturating_add(1));
+
+        let merged = self.intervals[start..end].iter().fold(interval, |acc, v| {
+            acc.union(v).expect("the intervals touch")
+        });
+        self.intervals.splice(start..end, [merged]);
+    }
+
+    pub fn remove(&mut self, interval: Interval) {
+        let start = self.intervals.partition_point(|v| v.to < interval.from);
+        let end = self.intervals.partition_point(|v| v.from <= interval.to);
+
+        let rest: Vec<Interval> = self.intervals[start..end]
+            .iter()
+            .flat_map(|v| v.difference(&interval))
+            .flatten()
+            .collect();
+        self.intervals.splice(start..end, rest);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        res.extend(other.intervals.iter().copied());
+        res
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (
+            self.intervals.iter().peekable(),
+            other.intervals.iter().peekable(),
+        );
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            intervals.extend(x.intersection(y));
+
+            match x.to < y.to {
+                true => a.next(),
+                false => b.next(),
+            };
+        }
+
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        other.intervals.iter().for_each(|v| res.remove(*v));
+        res
+    }
+
+    /// The integers of `bounds` that are not in the set
+    pub fn gaps(&self, bounds: Interval) -> Self {
+        let mut res = Self {
+            intervals: vec![bounds],
+        };
+        self.intervals.iter().for_each(|v| res.remove(*v));
+        res
+    }
+}
+
+impl Extend<Interval> for IntervalSet {
+    fn extend<I: IntoIterator<Item = Interval>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|v| self.insert(v));
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        Self {
+            intervals: merge(iter.into_iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(from: u64, to: u64) -> Interval {
+        Interval::new(from, to).unwrap()
+    }
+
+    #[test]
+    fn algebra() {
+        let (a, b) = (interval(2, 6), interval(4, 8));
+
+        assert_eq!(Interval::new(3, 2), None);
+        assert_eq!(a.len(), 5);
+        assert_eq!(interval(0, u64::MAX).len(), 1 << 64);
+        assert_eq!(a.intersection(&b), Some(interval(4, 6)));
+        assert_eq!(a.intersection(&interval(7, 9)), None);
+        assert_eq!(a.union(&b), Some(interval(2, 8)));
+        assert_eq!(a.union(&interval(7, 9)), Some(interval(2, 9)));
+        assert_eq!(a.union(&interval(8, 9)), None);
+        assert_eq!(a.difference(&b), [Some(interval(2, 3)), None]);
+        assert_eq!(
+            a.difference(&interval(3, 4)),
+            [Some(interval(2, 2)), Some(interval(5, 6))]
+        );
+        assert_eq!(a.difference(&interval(0, 9)), [None, None]);
+        assert_eq!(
+            interval(0, u64::MAX).difference(&interval(0, 0)),
+            [None, Some(interval(1, u64::MAX))]
+        );
+    }
+
+    #[test]
+    fn merging() {
+        let list = vec![
+            interval(5, 7),
+            interval(1, 2),
+            interval(3, 3),
+            interval(6, 9),
+            interval(11, 12),
+        ];
+        assert_eq!(
+            merge(list),
+            [interval(1, 3), interval(5, 9), interval(11, 12)]
+        );
+    }
+
+    #[test]
+    fn sets() {
+        let mut set: IntervalSet = [interval(1, 3), interval(8, 9)].into_iter().collect();
+        set.insert(interval(5, 5));
+        set.insert(interval(4, 4));
+        assert_eq!(set.intervals(), [interval(1, 5), interval(8, 9)]);
+        assert_eq!(set.coverage(), 7);
+        assert!(set.contains(9) && !set.contains(6));
+
+        set.remove(interval(2, 8));
+        assert_eq!(set.intervals(), [interval(1, 1), interval(9, 9)]);
+
+        let other: IntervalSet = [interval(1, 4), interval(6, 9)].into_iter().collect();
+        assert_eq!(set.intersection(&other), set);
+        assert_eq!(
+            other.difference(&set).intervals(),
+            [interval(2, 4), interval(6, 8)]
+        );
+        assert_eq!(other.union(&set), other);
+        assert_eq!(
+            other.gaps(interval(0, 10)).intervals(),
+            [interval(0, 0), interval(5, 5), interval(10, 10)]
+        );
+    }
+}
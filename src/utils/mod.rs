@@ -0,0 +1,58 @@
+mod bitset;
+mod grid;
+pub mod interval;
+pub mod parse;
+mod point;
+mod section;
+mod top_k;
+
+pub use bitset::BitSet;
+pub use grid::Grid;
+pub use interval::{Interval, IntervalSet};
+pub use parse::ParseError;
+pub use point::{Direction, Point, Vector};
+pub use section::{Section, Sections};
+pub use top_k::{top_k, TopK};
+
+use std::io::BufRead;
+use std::str::FromStr;
+
+pub fn lines_to_vec<T: FromStr>(s: &str) -> Option<Vec<T>> {
+    let mut list = Vec::new();
+
+    for data in s.lines() {
+        let value = data.parse::<T>().ok()?;
+        list.push(value);
+    }
+
+    Some(list)
+}
+
+pub fn string_to_vec<T: FromStr>(s: &str, seperator: &str) -> Option<Vec<T>> {
+    let mut list = Vec::new();
+
+    for data in s.split(seperator) {
+        let value = data.parse::<T>().ok()?;
+        list.push(value);
+    }
+
+    Some(list)
+}
+
+/// The lines of `reader` with their (1-based) numbers, cleaned up like a whole
+/// input would be by the runner: no byte order mark and no trailing whitespace
+pub fn stream_lines(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, String), ParseError>> {
+    reader.lines().zip(1..).map(|(line, number)| {
+        let mut line =
+            line.map_err(|e| ParseError::new(format!("cannot read the input: {e}")).at(number, 1))?;
+        line.truncate(line.trim_end().len());
+
+        if number == 1 && line.starts_with('\u{feff}') {
+            line.remove(0);
+        }
+
+        Ok((number, line))
+    })
+}
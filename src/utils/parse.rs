@@ -0,0 +1,423 @@
+//! A small parser combinator toolkit for the puzzle inputs
+//!
+//! A parser is anything that takes the remaining input and returns the parsed
+//! value together with the input that is left after it. Plain functions like
+//! [`unsigned`] are parsers themselves, the [`Parser`] methods combine them:
+//!
+//! ```ignore
+//! fn range(input: &str) -> ParseResult<'_, (u64, u64)> {
+//!     unsigned.skip(literal("-")).and(unsigned).parse(input)
+//! }
+//!
+//! let pairs = parse::all(input, lines(range.skip(literal(",")).and(range)))?;
+//! ```
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+pub type ParseResult<'a, T> = Result<(T, &'a str), Failure<'a>>;
+
+/// What a parser was looking for when it failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expected {
+    /// This exact text
+    Literal(&'static str),
+    /// A description like "a number"
+    Named(&'static str),
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(text) => write!(f, "`{}`", text.escape_debug()),
+            Self::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+/// A failed parser, `rest` is the input where it failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Failure<'a> {
+    pub rest: &'a str,
+    pub expected: Expected,
+}
+
+impl<'a> Failure<'a> {
+    pub fn new(rest: &'a str, expected: Expected) -> Self {
+        Self { rest, expected }
+    }
+
+    /// The failure that got further into the input
+    fn furthest(self, other: Self) -> Self {
+        match other.rest.len() < self.rest.len() {
+            true => other,
+            false => self,
+        }
+    }
+}
+
+/// An input that cannot be parsed, with the (1-based) position of the problem if it is known
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            position: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.position = Some((line, column));
+        self
+    }
+
+    /// Points at the start of `rest`, which has to be the end of `input`
+    pub fn located(input: &str, rest: &str, message: impl Into<String>) -> Self {
+        let consumed = &input[..input.len() - rest.len()];
+        let line = consumed.matches('\n').count() + 1;
+        let line_start = consumed.rfind('\n').map_or(0, |v| v + 1);
+        let column = consumed[line_start..].chars().count() + 1;
+
+        Self::new(message).at(line, column)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "line {line}, column {column}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+pub trait Parser<'a, T> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T>;
+
+    fn map<U>(self, f: impl Fn(T) -> U) -> impl Parser<'a, U>
+    where
+        Self: Sized,
+    {
+        move |input: &'a str| -> ParseResult<'a, U> {
+            let (value, rest) = self.parse(input)?;
+            Ok((f(value), rest))
+        }
+    }
+
+    /// Like `map`, but a `None` fails at the start of the value
+    fn try_map<U>(self, expected: &'static str, f: impl Fn(T) -> Option<U>) -> impl Parser<'a, U>
+    where
+        Self: Sized,
+    {
+        move |input: &'a str| -> ParseResult<'a, U> {
+            let (value, rest) = self.parse(input)?;
+            match f(value) {
+                Some(value) => Ok((value, rest)),
+                None => Err(Failure::new(input, Expected::Named(expected))),
+            }
+        }
+    }
+
+    /// Both values, one after the other
+    fn and<U>(self, other: impl Parser<'a, U>) -> impl Parser<'a, (T, U)>
+    where
+        Self: Sized,
+    {
+        move |input: &'a str| -> ParseResult<'a, (T, U)> {
+            let (a, rest) = self.parse(input)?;
+            let (b, rest) = other.parse(rest)?;
+            Ok(((a, b), rest))
+        }
+    }
+
+    /// Only the value of `other`, which comes after this one
+    fn then<U>(self, other: impl Parser<'a, U>) -> impl Parser<'a, U>
+    where
+        Self: Sized,
+    {
+        self.and(other).map(|(_, b)| b)
+    }
+
+    /// Only the value of this one, which comes before `other`
+    fn skip<U>(self, other: impl Parser<'a, U>) -> impl Parser<'a, T>
+    where
+        Self: Sized,
+    {
+        self.and(other).map(|(a, _)| a)
+    }
+
+    /// `other` when this one fails
+    fn or(self, other: impl Parser<'a, T>) -> impl Parser<'a, T>
+    where
+        Self: Sized,
+    {
+        move |input: &'a str| -> ParseResult<'a, T> {
+            self.parse(input)
+                .or_else(|a| other.parse(input).map_err(|b| a.furthest(b)))
+        }
+    }
+
+    fn optional(self) -> impl Parser<'a, Option<T>>
+    where
+        Self: Sized,
+    {
+        move |input: &'a str| -> ParseResult<'a, Option<T>> {
+            match self.parse(input) {
+                Ok((value, rest)) => Ok((Some(value), rest)),
+                Err(_) => Ok((None, input)),
+            }
+        }
+    }
+
+    /// Zero or more values
+    fn many(self) -> impl Parser<'a, Vec<T>>
+    where
+        Self: Sized,
+    {
+        move |mut input: &'a str| -> ParseResult<'a, Vec<T>> {
+            let mut res = Vec::new();
+
+            while let Ok((value, rest)) = self.parse(input) {
+                if rest.len() == input.len() {
+                    break;
+                }
+
+                res.push(value);
+                input = rest;
+            }
+
+            Ok((res, input))
+        }
+    }
+
+    /// One or more values with a `separator` between them, the list ends
+    /// before the first separator that is not followed by a value
+    fn sep_by<U>(self, separator: impl Parser<'a, U>) -> impl Parser<'a, Vec<T>>
+    where
+        Self: Sized,
+    {
+        move |input: &'a str| -> ParseResult<'a, Vec<T>> {
+            let (first, mut input) = self.parse(input)?;
+            let mut res = vec![first];
+
+            while let Ok((value, rest)) = separator.parse(input).and_then(|(_, v)| self.parse(v)) {
+                res.push(value);
+                input = rest;
+            }
+
+            Ok((res, input))
+        }
+    }
+}
+
+impl<'a, T, F> Parser<'a, T> for F
+where
+    F: Fn(&'a str) -> ParseResult<'a, T>,
+{
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+/// Runs `parser` on the whole input, only trailing whitespace may be left over
+pub fn all<'a, T>(input: &'a str, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+    let failure = match parser.parse(input) {
+        Ok((value, rest)) if rest.trim_end().is_empty() => return Ok(value),
+        Ok((_, rest)) => Failure::new(rest, Expected::Named("the end of the input")),
+        Err(failure) => failure,
+    };
+
+    let message = format!("expected {}", failure.expected);
+    Err(ParseError::located(input, failure.rest, message))
+}
+
+/// Exactly `text`
+pub fn literal<'a>(text: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| -> ParseResult<'a, &'a str> {
+        match input.starts_with(text) {
+            true => Ok(input.split_at(text.len())),
+            false => Err(Failure::new(input, Expected::Literal(text))),
+        }
+    }
+}
+
+/// The longest prefix of characters matching `f`, which cannot be empty
+fn take_while<'a>(
+    input: &'a str,
+    expected: &'static str,
+    f: impl Fn(char) -> bool,
+) -> ParseResult<'a, &'a str> {
+    let len = input.find(|v| !f(v)).unwrap_or(input.len());
+
+    match len {
+        0 => Err(Failure::new(input, Expected::Named(expected))),
+        len => Ok(input.split_at(len)),
+    }
+}
+
+/// A number made of decimal digits
+pub fn unsigned<T: FromStr>(input: &str) -> ParseResult<'_, T> {
+    let (digits, rest) = take_while(input, "a number", |v| v.is_ascii_digit())?;
+
+    match digits.parse() {
+        Ok(value) => Ok((value, rest)),
+        Err(_) => Err(Failure::new(input, Expected::Named("a smaller number"))),
+    }
+}
+
+/// A number made of decimal digits, with an optional `-` or `+` sign before it
+pub fn signed<T: FromStr>(input: &str) -> ParseResult<'_, T> {
+    let sign = input.starts_with(['-', '+']) as usize;
+    let (digits, rest) = take_while(&input[sign..], "a number", |v| v.is_ascii_digit())?;
+
+    match input[..sign + digits.len()].parse() {
+        Ok(value) => Ok((value, rest)),
+        Err(_) => Err(Failure::new(input, Expected::Named("a smaller number"))),
+    }
+}
+
+/// A single character, including a line break
+pub fn any_char(input: &str) -> ParseResult<'_, char> {
+    let mut chars = input.chars();
+
+    match chars.next() {
+        Some(ch) => Ok((ch, chars.as_str())),
+        None => Err(Failure::new(input, Expected::Named("a character"))),
+    }
+}
+
+/// One or more characters that are not whitespace
+pub fn word(input: &str) -> ParseResult<'_, &str> {
+    take_while(input, "a word", |v| !v.is_whitespace())
+}
+
+/// Zero or more spaces or tabs
+pub fn spaces(input: &str) -> ParseResult<'_, &str> {
+    let len = input.find(|v| v != ' ' && v != '\t').unwrap_or(input.len());
+    Ok(input.split_at(len))
+}
+
+/// `\n` or `\r\n`
+pub fn line_ending(input: &str) -> ParseResult<'_, &str> {
+    literal("\n").or(literal("\r\n")).parse(input)
+}
+
+/// Everything up to the end of the line, which can be empty
+pub fn rest_of_line(input: &str) -> ParseResult<'_, &str> {
+    let len = input.find(['\r', '\n']).unwrap_or(input.len());
+    Ok(input.split_at(len))
+}
+
+/// One `item` per line, up to the end of the input or a blank line
+///
+/// Every line has to be an `item` as a whole, the line break after the
+/// last one is left in the input. An empty input has no lines.
+pub fn lines<'a, T>(item: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: &'a str| -> ParseResult<'a, Vec<T>> {
+        let mut res = Vec::new();
+
+        if input.is_empty() {
+            return Ok((res, input));
+        }
+
+        loop {
+            let (value, rest) = item.parse(input)?;
+            res.push(value);
+            input = rest;
+
+            if input.is_empty() {
+                return Ok((res, input));
+            }
+
+            let Ok((_, next)) = line_ending(input) else {
+                return Err(Failure::new(input, Expected::Named("the end of the line")));
+            };
+
+            if next.is_empty() || line_ending(next).is_ok() {
+                return Ok((res, input));
+            }
+
+            input = next;
+        }
+    }
+}
+
+/// Groups of lines separated by blank lines, each of them is parsed by `block`
+pub fn blocks<'a, T>(block: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    block.sep_by(
+        line_ending
+            .and(line_ending)
+            .and(spaces.and(line_ending).many()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(input: &str) -> ParseResult<'_, (u32, u32)> {
+        unsigned.skip(literal("-")).and(unsigned).parse(input)
+    }
+
+    #[test]
+    fn combinators() {
+        let pair = range.skip(literal(",")).and(range);
+        assert_eq!(all("2-4,6-8", pair), Ok(((2, 4), (6, 8))));
+
+        let instruction = literal("noop")
+            .map(|_| None)
+            .or(literal("addx ").then(signed::<i32>).map(Some));
+        assert_eq!(
+            all("noop\naddx -5\naddx +3\n", lines(instruction)),
+            Ok(vec![None, Some(-5), Some(3)])
+        );
+
+        let list = unsigned::<u8>.sep_by(literal(", "));
+        assert_eq!(all("1, 2, 3", list), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn empty_lines() {
+        assert_eq!(all("", lines(unsigned::<u8>)), Ok(vec![]));
+        assert!(all("\n1", lines(unsigned::<u8>)).is_err());
+    }
+
+    #[test]
+    fn blank_line_blocks() {
+        let input = "1\n2\n\n3\r\n\r\n4\n";
+        let res = all(input, blocks(lines(unsigned::<u8>)));
+        assert_eq!(res, Ok(vec![vec![1, 2], vec![3], vec![4]]));
+    }
+
+    #[test]
+    fn error_positions() {
+        let res = all("1\n2\nx3\n", lines(unsigned::<u8>));
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "line 3, column 1: expected a number"
+        );
+
+        let res = all(
+            "move 1 to 2",
+            literal("move ").then(word).skip(literal(" from ")),
+        );
+        assert_eq!(res.unwrap_err().position, Some((1, 7)));
+
+        let res = all("1\n2 3\n", lines(unsigned::<u8>));
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "line 2, column 2: expected the end of the line"
+        );
+
+        let res = all("300", unsigned::<u8>);
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "line 1, column 1: expected a smaller number"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A position on a plane where `x` grows to the right and `y` grows downwards,
+/// the same way as the rows of a puzzle input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+/// The difference between two points
+pub type Vector = Point;
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// Distance when only moving horizontally or vertically
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Distance when diagonal moves are allowed too
+    pub fn chebyshev(self, other: Self) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// A single step (of at most 1 on each axis) towards the same direction
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl From<(isize, isize)> for Point {
+    fn from((x, y): (isize, isize)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: isize) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// The 8 directions in clockwise order, starting from north (up)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// Up, right, down and left
+    pub const CARDINAL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    pub fn vector(self) -> Vector {
+        match self {
+            Self::North => Vector::new(0, -1),
+            Self::NorthEast => Vector::new(1, -1),
+            Self::East => Vector::new(1, 0),
+            Self::SouthEast => Vector::new(1, 1),
+            Self::South => Vector::new(0, 1),
+            Self::SouthWest => Vector::new(-1, 1),
+            Self::West => Vector::new(-1, 0),
+            Self::NorthWest => Vector::new(-1, -1),
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// Turns clockwise by `steps` eighths of a full turn, negative steps turn counterclockwise
+    pub fn rotate(self, steps: isize) -> Self {
+        Self::ALL[(self as isize + steps).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(-2)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.rotate(4)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    /// Either `U`, `D`, `L`, `R` or a compass direction like `N` or `SW`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" | "N" => Ok(Self::North),
+            "NE" => Ok(Self::NorthEast),
+            "R" | "E" => Ok(Self::East),
+            "SE" => Ok(Self::SouthEast),
+            "D" | "S" => Ok(Self::South),
+            "SW" => Ok(Self::SouthWest),
+            "L" | "W" => Ok(Self::West),
+            "NW" => Ok(Self::NorthWest),
+            _ => Err(format!("`{s}` is not a direction")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        let a = Point::new(1, -2);
+        let b = Point::new(-3, 4);
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!((b - a).signum(), Point::new(-1, 1));
+        assert_eq!(a + b * 2, Point::new(-5, 6));
+    }
+
+    #[test]
+    fn rotation() {
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::West.rotate(1), Direction::NorthWest);
+        assert_eq!(Direction::SouthEast.opposite(), Direction::NorthWest);
+        assert!(Direction::SouthWest.is_diagonal());
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("U".parse(), Ok(Direction::North));
+        assert_eq!("W".parse(), Ok(Direction::West));
+        assert_eq!("SE".parse(), Ok(Direction::SouthEast));
+        assert!("X".parse::<Direction>().is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use super::parse::{self, Parser};
+use super::ParseError;
+use std::mem;
+
+/// A group of lines between blank lines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// The lines of the section, without the line break after the last one
+    pub text: &'a str,
+    /// Where the section starts in the whole input, 1-based
+    pub line: usize,
+}
+
+impl<'a> Section<'a> {
+    /// Parses the whole section, the position of an error is the one in the whole input
+    pub fn parse<T>(&self, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+        parse::all(self.text, parser).map_err(|mut e| {
+            if let Some((line, column)) = e.position {
+                e.position = Some((line + self.line - 1, column));
+            }
+
+            e
+        })
+    }
+}
+
+/// The sections of an input, any number of blank (or whitespace only) lines
+/// separates them and both `\n` and `\r\n` line breaks are accepted
+pub struct Sections<'a> {
+    rest: &'a str,
+    line: usize,
+    /// See `with_empty`
+    empty: bool,
+    /// The last section ended at the blank line `rest` starts with
+    ended: bool,
+}
+
+impl<'a> Sections<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            rest: input,
+            line: 1,
+            empty: false,
+            ended: false,
+        }
+    }
+
+    /// A single blank line separates the sections and every other blank line is
+    /// an empty section, except the blank lines at the end of the input
+    pub fn with_empty(mut self) -> Self {
+        self.empty = true;
+        self
+    }
+
+    /// Parses the next section with `parser`
+    pub fn parse<T>(&mut self, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+        match self.next() {
+            Some(section) => section.parse(parser),
+            None => Err(ParseError::new("expected another section").at(self.line, 1)),
+        }
+    }
+
+    /// Makes sure that every section has been parsed
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        match self.next() {
+            Some(section) => {
+                Err(ParseError::new("expected the end of the input").at(section.line, 1))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The next line without its line break, and the input after it
+    fn split_line(&self) -> Option<(&'a str, &'a str)> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        Some(match self.rest.find('\n') {
+            Some(idx) => (&self.rest[..idx], &self.rest[idx + 1..]),
+            None => (self.rest, ""),
+        })
+    }
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = Section<'a>;
+
+    fn next(&mut self) -> Option<Section<'a>> {
+        if self.empty && !self.rest.trim().is_empty() {
+            if mem::take(&mut self.ended) {
+                self.rest = self.split_line().map_or("", |(_, rest)| rest);
+                self.line += 1;
+            }
+
+            if let Some((line, rest)) = self.split_line() {
+                if line.trim().is_empty() {
+                    let section = Section {
+                        text: "",
+                        line: self.line,
+                    };
+                    self.rest = rest;
+                    self.line += 1;
+                    return Some(section);
+                }
+            }
+        }
+
+        while let Some((line, rest)) = self.split_line() {
+            if !line.trim().is_empty() {
+                break;
+            }
+
+            self.rest = rest;
+            self.line += 1;
+        }
+
+        let start = self.rest;
+        let first_line = self.line;
+        let mut len = 0;
+
+        while let Some((line, rest)) = self.split_line() {
+            if line.trim().is_empty() {
+                break;
+            }
+
+            len = start.len() - self.rest.len() + line.trim_end_matches('\r').len();
+            self.rest = rest;
+            self.line += 1;
+        }
+
+        self.ended = len > 0;
+        (len > 0).then(|| Section {
+            text: &start[..len],
+            line: first_line,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse::{lines, unsigned};
+
+    #[test]
+    fn split() {
+        let input = "1\r\n2\r\n\r\n\r\n3\n  \n4\n\n";
+        let sections: Vec<_> = Sections::new(input).map(|v| (v.text, v.line)).collect();
+        assert_eq!(sections, [("1\r\n2", 1), ("3", 5), ("4", 7)]);
+    }
+
+    #[test]
+    fn empty_sections() {
+        let input = "\n1\n2\n\n\r\n3\n\n4\n\n\n\n";
+        let sections: Vec<_> = Sections::new(input)
+            .with_empty()
+            .map(|v| (v.text, v.line))
+            .collect();
+        assert_eq!(
+            sections,
+            [("", 1), ("1\n2", 2), ("", 5), ("3", 6), ("4", 8)]
+        );
+    }
+
+    #[test]
+    fn located_errors() {
+        let mut sections = Sections::new("1\n2\n\n3\nx\n");
+        assert_eq!(sections.parse(lines(unsigned::<u8>)), Ok(vec![1, 2]));
+
+        let e = sections.parse(lines(unsigned::<u8>)).unwrap_err();
+        assert_eq!(e.to_string(), "line 5, column 1: expected a number");
+
+        let e = sections.parse(lines(unsigned::<u8>)).unwrap_err();
+        assert_eq!(e.position, Some((6, 1)));
+    }
+}
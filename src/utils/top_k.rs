@@ -0,0 +1,65 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Keeps the `k` largest items pushed into it, with at most `k` of them in memory
+pub struct TopK<T> {
+    k: usize,
+    /// A min-heap, so the smallest kept item is the one to replace
+    heap: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopK<T> {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(item));
+        } else if let Some(mut smallest) = self.heap.peek_mut() {
+            if item > smallest.0 {
+                *smallest = Reverse(item);
+            }
+        }
+    }
+
+    /// The kept items from the largest, fewer than `k` when fewer were pushed
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|v| v.0)
+            .collect()
+    }
+}
+
+impl<T: Ord> Extend<T> for TopK<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|v| self.push(v));
+    }
+}
+
+/// The `k` largest items, from the largest
+pub fn top_k<T: Ord>(iter: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    let mut top = TopK::new(k);
+    top.extend(iter);
+    top.into_sorted_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest() {
+        let list = [5, 1, 9, 3, 9, 7];
+
+        assert_eq!(top_k(list, 3), [9, 9, 7]);
+        assert_eq!(top_k(list, 10), [9, 9, 7, 5, 3, 1]);
+        assert_eq!(top_k(list, 0), Vec::<i32>::new());
+        assert_eq!(top_k(Vec::<i32>::new(), 2), Vec::<i32>::new());
+    }
+}